@@ -0,0 +1,175 @@
+use crate::erro_handle::{ErroHandle, RetroLogLevel};
+use sevenz_rust::{Password, SevenZReader};
+use std::{
+    fs::File,
+    io::{self, Read},
+    path::Path,
+};
+use zip::ZipArchive;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveKind {
+    Zip,
+    SevenZip,
+}
+
+impl ArchiveKind {
+    pub fn from_path(path: &Path) -> Option<ArchiveKind> {
+        let extension = path.extension()?.to_string_lossy().to_lowercase();
+
+        match extension.as_str() {
+            "zip" => Some(ArchiveKind::Zip),
+            "7z" => Some(ArchiveKind::SevenZip),
+            _ => None,
+        }
+    }
+}
+
+fn archive_error(path: &Path, message: String) -> ErroHandle {
+    ErroHandle {
+        level: RetroLogLevel::RETRO_LOG_ERROR,
+        message: "Nao foi possível ler o arquivo compactado ".to_string()
+            + &path.display().to_string()
+            + ": "
+            + &message,
+    }
+}
+
+fn open_zip(path: &Path) -> Result<ZipArchive<File>, ErroHandle> {
+    let file = File::open(path).map_err(|e| archive_error(path, e.to_string()))?;
+
+    ZipArchive::new(file).map_err(|e| archive_error(path, e.to_string()))
+}
+
+fn open_7z(path: &Path) -> Result<SevenZReader<File>, ErroHandle> {
+    SevenZReader::open(path, Password::empty()).map_err(|e| archive_error(path, e.to_string()))
+}
+
+#[doc = "Lista os arquivos dentro de `path` na ordem em que aparecem, pastas sao ignoradas."]
+pub fn list_entries(path: &Path) -> Result<Vec<String>, ErroHandle> {
+    match ArchiveKind::from_path(path) {
+        Some(ArchiveKind::Zip) => {
+            let mut archive = open_zip(path)?;
+            let mut entries = Vec::new();
+
+            for index in 0..archive.len() {
+                let file = archive
+                    .by_index(index)
+                    .map_err(|e| archive_error(path, e.to_string()))?;
+
+                if !file.is_dir() {
+                    entries.push(file.name().to_string());
+                }
+            }
+
+            Ok(entries)
+        }
+        Some(ArchiveKind::SevenZip) => Ok(open_7z(path)?
+            .archive()
+            .files
+            .iter()
+            .filter(|file| !file.is_directory())
+            .map(|file| file.name().to_string())
+            .collect()),
+        None => Err(archive_error(path, "formato nao suportado".to_string())),
+    }
+}
+
+pub fn read_entry(path: &Path, entry: &str) -> Result<Vec<u8>, ErroHandle> {
+    let mut data = Vec::new();
+
+    match ArchiveKind::from_path(path) {
+        Some(ArchiveKind::Zip) => {
+            let mut archive = open_zip(path)?;
+            let mut file = archive
+                .by_name(entry)
+                .map_err(|e| archive_error(path, e.to_string()))?;
+
+            file.read_to_end(&mut data)
+                .map_err(|e| archive_error(path, e.to_string()))?;
+        }
+        Some(ArchiveKind::SevenZip) => {
+            let mut found = false;
+
+            open_7z(path)?
+                .for_each_entries(|file, reader| {
+                    if file.name() != entry {
+                        //em arquivos solid os dados precisam ser consumidos em ordem
+                        io::copy(reader, &mut io::sink())?;
+                        return Ok(true);
+                    }
+
+                    reader.read_to_end(&mut data)?;
+                    found = true;
+
+                    Ok(false)
+                })
+                .map_err(|e| archive_error(path, e.to_string()))?;
+
+            if !found {
+                return Err(archive_error(
+                    path,
+                    "arquivo ".to_string() + entry + " nao encontrado",
+                ));
+            }
+        }
+        None => return Err(archive_error(path, "formato nao suportado".to_string())),
+    }
+
+    Ok(data)
+}
+
+#[cfg(test)]
+mod test_archive {
+    use crate::archive::{list_entries, read_entry, ArchiveKind};
+    use std::{fs, io::Write, path::Path};
+    use zip::{write::FileOptions, ZipWriter};
+
+    #[test]
+    fn zip_entries() {
+        fs::create_dir_all("retro_out_test/archive").unwrap();
+        let path = Path::new("retro_out_test/archive/games.zip");
+
+        let mut writer = ZipWriter::new(fs::File::create(path).unwrap());
+        writer
+            .add_directory("docs/", FileOptions::default())
+            .unwrap();
+        writer
+            .start_file("docs/readme.txt", FileOptions::default())
+            .unwrap();
+        writer.write_all(b"leia").unwrap();
+        writer
+            .start_file("game.sfc", FileOptions::default())
+            .unwrap();
+        writer.write_all(&[1, 2, 3]).unwrap();
+        writer.finish().unwrap();
+
+        assert_eq!(ArchiveKind::from_path(path), Some(ArchiveKind::Zip));
+        assert_eq!(
+            list_entries(path).unwrap(),
+            vec!["docs/readme.txt".to_string(), "game.sfc".to_string()]
+        );
+        assert_eq!(read_entry(path, "game.sfc").unwrap(), vec![1, 2, 3]);
+        assert!(read_entry(path, "outro.sfc").is_err());
+    }
+
+    #[test]
+    fn seven_zip_entries() {
+        let src = Path::new("retro_out_test/archive/7z_src");
+        fs::create_dir_all(src).unwrap();
+        fs::write(src.join("a.txt"), b"texto").unwrap();
+        fs::write(src.join("game.gba"), [4, 5, 6, 7]).unwrap();
+
+        let path = Path::new("retro_out_test/archive/games.7z");
+        sevenz_rust::compress_to_path(src, path).unwrap();
+
+        let entries = list_entries(path).unwrap();
+        assert_eq!(entries.len(), 2);
+
+        let game = entries.iter().find(|e| e.ends_with("game.gba")).unwrap();
+        assert_eq!(read_entry(path, game).unwrap(), vec![4, 5, 6, 7]);
+
+        let text = entries.iter().find(|e| e.ends_with("a.txt")).unwrap();
+        assert_eq!(read_entry(path, text).unwrap(), b"texto".to_vec());
+    }
+}
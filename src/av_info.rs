@@ -1,9 +1,13 @@
 use crate::graphic_api::GraphicApi;
 use crate::retro_sys::LibretroRaw;
 use crate::{
-    binding::binding_libretro::{retro_game_geometry, retro_system_av_info, retro_system_timing},
+    binding::binding_libretro::{
+        retro_audio_buffer_status_callback_t, retro_framebuffer, retro_game_geometry,
+        retro_system_av_info, retro_system_timing, RETRO_MEMORY_TYPE_CACHED,
+    },
     core::retro_pixel_format,
 };
+use std::os::raw::c_void;
 use std::sync::{Arc, Mutex, RwLock};
 
 #[derive(Default, Debug)]
@@ -42,6 +46,8 @@ pub struct Video {
     pub pixel_format: Mutex<retro_pixel_format>,
     pub geometry: Geometry,
     pub graphic_api: GraphicApi,
+    #[doc = "Buffer entregue ao núcleo em RETRO_ENVIRONMENT_GET_CURRENT_SOFTWARE_FRAMEBUFFER."]
+    pub software_framebuffer: Mutex<Vec<u8>>,
 }
 
 impl Default for Video {
@@ -51,6 +57,106 @@ impl Default for Video {
             pixel_format: Mutex::new(retro_pixel_format::RETRO_PIXEL_FORMAT_UNKNOWN),
             geometry: Geometry::default(),
             graphic_api: GraphicApi::default(),
+            software_framebuffer: Mutex::new(Vec::new()),
+        }
+    }
+}
+
+impl Video {
+    #[doc = "Se o núcleo nao escolheu um formato o padrão do libretro e 0RGB1555."]
+    pub fn get_pixel_format(&self) -> retro_pixel_format {
+        match *self.pixel_format.lock().unwrap() {
+            retro_pixel_format::RETRO_PIXEL_FORMAT_UNKNOWN => {
+                retro_pixel_format::RETRO_PIXEL_FORMAT_0RGB1555
+            }
+            pixel_format => pixel_format,
+        }
+    }
+
+    pub fn get_bytes_per_pixel(&self) -> usize {
+        match self.get_pixel_format() {
+            retro_pixel_format::RETRO_PIXEL_FORMAT_XRGB8888 => 4,
+            _ => 2,
+        }
+    }
+
+    #[doc = "
+        Preenche `framebuffer` com um buffer do tamanho de max_width x max_height, o núcleo
+        pode desenhar direto nele e depois enviar o mesmo ponteiro para video_refresh.
+        Retorna falso se o núcleo pedir um tamanho maior que a geometria máxima ou se a
+        geometria ainda nao foi definida.
+
+        O crate apenas evita que o núcleo precise do próprio buffer, o ponteiro chega sem cópia
+        em `video_refresh_callback` e cabe ao frontend decidir se copia o frame ou nao.
+    "]
+    pub fn get_software_framebuffer(&self, framebuffer: &mut retro_framebuffer) -> bool {
+        let max_width = *self.geometry.max_width.read().unwrap();
+        let max_height = *self.geometry.max_height.read().unwrap();
+
+        if framebuffer.width > max_width || framebuffer.height > max_height {
+            return false;
+        }
+
+        let pitch = max_width as usize * self.get_bytes_per_pixel();
+        let size = pitch * max_height as usize;
+
+        if size == 0 {
+            return false;
+        }
+
+        let mut buffer = self.software_framebuffer.lock().unwrap();
+
+        //so realoca quando a geometria ou o formato mudam, assim o ponteiro continua valido
+        if buffer.len() != size {
+            *buffer = vec![0; size];
+        }
+
+        framebuffer.data = buffer.as_mut_ptr() as *mut c_void;
+        framebuffer.pitch = pitch;
+        framebuffer.format = self.get_pixel_format();
+        framebuffer.memory_flags = RETRO_MEMORY_TYPE_CACHED;
+
+        true
+    }
+
+    #[doc = "
+        Verdadeiro se o frame recebido em video_refresh foi desenhado no buffer do frontend.
+        Nesse caso os dados continuam validos ate a geometria ou o formato de pixel mudarem.
+    "]
+    pub fn is_software_framebuffer(&self, data: *const c_void) -> bool {
+        let buffer = self.software_framebuffer.lock().unwrap();
+
+        !buffer.is_empty() && buffer.as_ptr() as *const c_void == data
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct AudioBufferStatus {
+    #[doc = "Verdadeiro enquanto o frontend estiver tocando o áudio recebido do núcleo."]
+    pub active: bool,
+    #[doc = "Quanto do buffer de áudio esta preenchido, de 0 a 100."]
+    pub occupancy: u32,
+    #[doc = "Verdadeiro se o buffer de áudio deve esvaziar em breve."]
+    pub underrun_likely: bool,
+}
+
+#[derive(Debug, Default)]
+pub struct Audio {
+    #[doc = "Estado informado pelo frontend, repassado ao núcleo a cada run."]
+    pub buffer_status: Mutex<AudioBufferStatus>,
+    pub buffer_status_callback: Mutex<retro_audio_buffer_status_callback_t>,
+    #[doc = "Latência mínima em milissegundos pedida pelo núcleo, 0 se o núcleo nao pediu nada."]
+    pub minimum_latency: Mutex<u32>,
+}
+
+impl Audio {
+    pub fn call_buffer_status_callback(&self) {
+        let callback = *self.buffer_status_callback.lock().unwrap();
+
+        if let Some(callback) = callback {
+            let status = *self.buffer_status.lock().unwrap();
+
+            unsafe { callback(status.active, status.occupancy, status.underrun_likely) }
         }
     }
 }
@@ -58,6 +164,7 @@ impl Default for Video {
 #[derive(Debug)]
 pub struct AvInfo {
     pub video: Video,
+    pub audio: Audio,
     pub timing: Timing,
 }
 
@@ -68,6 +175,7 @@ impl AvInfo {
                 graphic_api,
                 ..Default::default()
             },
+            audio: Audio::default(),
             timing: Timing::default(),
         }
     }
@@ -131,3 +239,54 @@ impl AvInfo {
         self._set_timing(&raw_av_info.timing);
     }
 }
+
+#[cfg(test)]
+mod test_av_info {
+    use crate::{
+        av_info::Video,
+        retro_sys::{retro_framebuffer, retro_pixel_format},
+    };
+    use std::ptr;
+
+    fn get_framebuffer(width: u32, height: u32) -> retro_framebuffer {
+        retro_framebuffer {
+            data: ptr::null_mut(),
+            width,
+            height,
+            pitch: 0,
+            format: retro_pixel_format::RETRO_PIXEL_FORMAT_UNKNOWN,
+            access_flags: 0,
+            memory_flags: 0,
+        }
+    }
+
+    #[test]
+    fn software_framebuffer() {
+        let video = Video::default();
+        *video.geometry.max_width.write().unwrap() = 320;
+        *video.geometry.max_height.write().unwrap() = 240;
+        *video.pixel_format.lock().unwrap() = retro_pixel_format::RETRO_PIXEL_FORMAT_XRGB8888;
+
+        let mut framebuffer = get_framebuffer(256, 224);
+
+        assert!(video.get_software_framebuffer(&mut framebuffer));
+        assert_eq!(framebuffer.pitch, 320 * 4);
+        assert_eq!(
+            framebuffer.format,
+            retro_pixel_format::RETRO_PIXEL_FORMAT_XRGB8888
+        );
+        assert!(video.is_software_framebuffer(framebuffer.data));
+
+        let data = framebuffer.data;
+        assert!(video.get_software_framebuffer(&mut framebuffer));
+        assert_eq!(framebuffer.data, data);
+
+        assert!(!video.get_software_framebuffer(&mut get_framebuffer(640, 480)));
+
+        let empty = Video::default();
+        let mut framebuffer = get_framebuffer(0, 0);
+        assert!(!empty.get_software_framebuffer(&mut framebuffer));
+        assert!(framebuffer.data.is_null());
+        assert!(!video.is_software_framebuffer(ptr::null()));
+    }
+}
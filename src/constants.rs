@@ -3,3 +3,8 @@ pub const MAX_CORE_CONTROLLER_INFO_TYPES: usize = 10;
 pub const MAX_CORE_SUBSYSTEM_INFO: usize = 40;
 pub const MAX_CORE_SUBSYSTEM_ROM_INFO: usize = 40;
 pub const CORE_OPTION_EXTENSION_FILE: &str = ".opt";
+pub const CORE_REMAP_EXTENSION_FILE: &str = ".rmp";
+pub const CHEAT_EXTENSION_FILE: &str = ".cht";
+pub const MAX_CORE_MESSAGES: usize = 64;
+pub const MAX_CORE_PORTS: usize = 16;
+pub const MAX_CORE_LEDS: usize = 32;
@@ -0,0 +1,534 @@
+use crate::{
+    content_identity::ContentIdentity,
+    erro_handle::{ErroHandle, RetroLogLevel},
+    retro_sys::{retro_game_info, retro_game_info_ext, retro_system_content_info_override},
+    tools::ffi_tools::{get_str_from_ptr, make_c_string},
+};
+use std::{
+    ffi::{CString, OsStr},
+    fs,
+    os::raw::{c_char, c_void},
+    path::{Path, PathBuf},
+    ptr::null,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContentInfoOverride {
+    pub extensions: Vec<String>,
+    pub need_fullpath: bool,
+    #[doc = "Se verdadeiro o buffer da rom deve continuar valido ate a rom ser descarregada."]
+    pub persistent_data: bool,
+}
+
+impl ContentInfoOverride {
+    #[doc = "Lê a lista enviada em RETRO_ENVIRONMENT_SET_CONTENT_INFO_OVERRIDE, ela termina com extensions nulo."]
+    pub(crate) unsafe fn from_raw_list(
+        raw: *const retro_system_content_info_override,
+    ) -> Vec<ContentInfoOverride> {
+        let mut overrides = Vec::new();
+
+        if raw.is_null() {
+            return overrides;
+        }
+
+        let mut index = 0;
+
+        loop {
+            let raw_override = *raw.add(index);
+
+            if raw_override.extensions.is_null() {
+                break;
+            }
+
+            overrides.push(ContentInfoOverride {
+                extensions: get_str_from_ptr(raw_override.extensions)
+                    .split('|')
+                    .map(|ext| ext.to_lowercase())
+                    .collect(),
+                need_fullpath: raw_override.need_fullpath,
+                persistent_data: raw_override.persistent_data,
+            });
+
+            index += 1;
+        }
+
+        overrides
+    }
+
+    pub fn find<'a>(
+        overrides: &'a [ContentInfoOverride],
+        extension: &str,
+    ) -> Option<&'a ContentInfoOverride> {
+        let extension = extension.to_lowercase();
+
+        overrides
+            .iter()
+            .find(|content_override| content_override.extensions.contains(&extension))
+    }
+}
+
+fn content_error(message: String) -> ErroHandle {
+    ErroHandle {
+        level: RetroLogLevel::RETRO_LOG_ERROR,
+        message,
+    }
+}
+
+fn write_temp_file(temp_dir: &Path, file_name: &OsStr, data: &[u8]) -> Result<PathBuf, ErroHandle> {
+    let temp_file = temp_dir.join(file_name);
+
+    fs::create_dir_all(temp_dir)
+        .and_then(|_| fs::write(&temp_file, data))
+        .map_err(|e| {
+            content_error(
+                "Nao foi possível escrever a rom em ".to_string()
+                    + &temp_file.display().to_string()
+                    + ": "
+                    + &e.to_string(),
+            )
+        })?;
+
+    Ok(temp_file)
+}
+
+#[doc = "
+    # Conteúdo carregado
+
+    Guarda tudo que foi enviado ao núcleo em retro_load_game, os ponteiros de
+    [retro_game_info] e [retro_game_info_ext] apontam para dentro dessa estrutura,
+    por isso ela deve existir ate a rom ser descarregada.
+"]
+#[derive(Debug)]
+pub struct LoadedContent {
+    pub full_path: CString,
+    pub dir: CString,
+    pub name: CString,
+    pub ext: CString,
+    pub meta: CString,
+    pub archive_path: Option<CString>,
+    pub archive_file: Option<CString>,
+    pub data: Vec<u8>,
+    pub need_fullpath: bool,
+    pub persistent_data: bool,
+    #[doc = "Arquivo extraído de um zip/7z para núcleos com need_fullpath, apagado junto com o conteúdo."]
+    pub temp_file: Option<PathBuf>,
+}
+
+impl LoadedContent {
+    pub fn new(
+        path: &Path,
+        need_fullpath: bool,
+        persistent_data: bool,
+    ) -> Result<LoadedContent, ErroHandle> {
+        let to_c_string =
+            |value: Option<&OsStr>| make_c_string(&value.unwrap_or_default().to_string_lossy());
+
+        let data = if need_fullpath {
+            Vec::new()
+        } else {
+            fs::read(path).map_err(|e| {
+                content_error(
+                    "Nao foi possível ler a rom ".to_string()
+                        + &path.display().to_string()
+                        + ": "
+                        + &e.to_string(),
+                )
+            })?
+        };
+
+        Ok(LoadedContent {
+            full_path: to_c_string(Some(path.as_os_str()))?,
+            dir: to_c_string(path.parent().map(|dir| dir.as_os_str()))?,
+            name: to_c_string(path.file_stem())?,
+            ext: make_c_string(
+                &path
+                    .extension()
+                    .unwrap_or_default()
+                    .to_string_lossy()
+                    .to_lowercase(),
+            )?,
+            meta: CString::default(),
+            archive_path: None,
+            archive_file: None,
+            data,
+            need_fullpath,
+            persistent_data,
+            temp_file: None,
+        })
+    }
+
+    #[doc = "
+        Conteúdo extraído de `entry` dentro de `archive_path`. Se o núcleo precisar de um
+        caminho o arquivo e escrito em `temp_dir`, se nao o buffer e enviado direto.
+    "]
+    pub fn from_archive(
+        archive_path: &Path,
+        entry: &str,
+        data: Vec<u8>,
+        need_fullpath: bool,
+        persistent_data: bool,
+        temp_dir: &Path,
+    ) -> Result<LoadedContent, ErroHandle> {
+        let entry_path = Path::new(entry);
+        let file_name = entry_path.file_name().unwrap_or_default();
+
+        let (full_path, data, temp_file) = if need_fullpath {
+            let temp_file = write_temp_file(temp_dir, file_name, &data)?;
+
+            (temp_file.display().to_string(), Vec::new(), Some(temp_file))
+        } else {
+            //mesmo formato usado pelo RetroArch para roms dentro de arquivos compactados
+            (archive_path.display().to_string() + "#" + entry, data, None)
+        };
+
+        Ok(LoadedContent {
+            full_path: make_c_string(&full_path)?,
+            dir: make_c_string(
+                &archive_path
+                    .parent()
+                    .map(|dir| dir.display().to_string())
+                    .unwrap_or_default(),
+            )?,
+            name: make_c_string(&entry_path.file_stem().unwrap_or_default().to_string_lossy())?,
+            ext: make_c_string(
+                &entry_path
+                    .extension()
+                    .unwrap_or_default()
+                    .to_string_lossy()
+                    .to_lowercase(),
+            )?,
+            meta: CString::default(),
+            archive_path: Some(make_c_string(&archive_path.display().to_string())?),
+            archive_file: Some(make_c_string(entry)?),
+            data,
+            need_fullpath,
+            persistent_data,
+            temp_file,
+        })
+    }
+
+    #[doc = "
+        Conteúdo que ja esta na memoria, `virtual_path` so informa o nome e a extensão ao
+        núcleo. Se o núcleo precisar de um caminho o buffer e escrito em `temp_dir`.
+    "]
+    pub fn from_memory(
+        virtual_path: &Path,
+        data: Vec<u8>,
+        meta: &str,
+        need_fullpath: bool,
+        persistent_data: bool,
+        temp_dir: &Path,
+    ) -> Result<LoadedContent, ErroHandle> {
+        let file_name = virtual_path.file_name().unwrap_or_default();
+
+        let (full_path, data, temp_file) = if need_fullpath {
+            let temp_file = write_temp_file(temp_dir, file_name, &data)?;
+
+            (temp_file.display().to_string(), Vec::new(), Some(temp_file))
+        } else {
+            (virtual_path.display().to_string(), data, None)
+        };
+
+        Ok(LoadedContent {
+            full_path: make_c_string(&full_path)?,
+            dir: make_c_string(
+                &virtual_path
+                    .parent()
+                    .map(|dir| dir.display().to_string())
+                    .unwrap_or_default(),
+            )?,
+            name: make_c_string(
+                &virtual_path
+                    .file_stem()
+                    .unwrap_or_default()
+                    .to_string_lossy(),
+            )?,
+            ext: make_c_string(
+                &virtual_path
+                    .extension()
+                    .unwrap_or_default()
+                    .to_string_lossy()
+                    .to_lowercase(),
+            )?,
+            meta: make_c_string(meta)?,
+            archive_path: None,
+            archive_file: None,
+            data,
+            need_fullpath,
+            persistent_data,
+            temp_file,
+        })
+    }
+
+    #[doc = "Usado no lugar de uma rom opcional que nao foi escolhida em load_game_special."]
+    pub fn empty() -> LoadedContent {
+        LoadedContent {
+            full_path: CString::default(),
+            dir: CString::default(),
+            name: CString::default(),
+            ext: CString::default(),
+            meta: CString::default(),
+            archive_path: None,
+            archive_file: None,
+            data: Vec::new(),
+            need_fullpath: false,
+            persistent_data: false,
+            temp_file: None,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.full_path.is_empty()
+    }
+
+    pub fn get_name(&self) -> String {
+        self.name.to_string_lossy().to_string()
+    }
+
+    #[doc = "Usa o buffer ja carregado quando existir, se nao lê o arquivo em blocos."]
+    pub fn get_identity(&self) -> Result<ContentIdentity, ErroHandle> {
+        let ext = self.ext.to_string_lossy();
+
+        //sem need_fullpath `data` e a rom inteira, mesmo vazia ou sem arquivo no disco
+        if self.need_fullpath {
+            ContentIdentity::from_path(Path::new(&*self.full_path.to_string_lossy()))
+        } else {
+            Ok(ContentIdentity::from_bytes(&self.data, &ext))
+        }
+    }
+
+    fn get_path_ptr(&self) -> *const c_char {
+        if self.is_empty() {
+            null()
+        } else {
+            self.full_path.as_ptr()
+        }
+    }
+
+    fn get_data_ptr(&self) -> *const c_void {
+        if self.data.is_empty() {
+            null()
+        } else {
+            self.data.as_ptr() as *const c_void
+        }
+    }
+
+    pub fn to_game_info(&self) -> retro_game_info {
+        retro_game_info {
+            path: self.get_path_ptr(),
+            data: self.get_data_ptr(),
+            size: self.data.len(),
+            meta: self.meta.as_ptr(),
+        }
+    }
+
+    pub fn to_game_info_ext(&self) -> retro_game_info_ext {
+        let opt_ptr = |value: &Option<CString>| match value {
+            Some(value) => value.as_ptr(),
+            None => null(),
+        };
+
+        retro_game_info_ext {
+            full_path: self.get_path_ptr(),
+            archive_path: opt_ptr(&self.archive_path),
+            archive_file: opt_ptr(&self.archive_file),
+            dir: self.dir.as_ptr(),
+            name: self.name.as_ptr(),
+            ext: self.ext.as_ptr(),
+            meta: self.meta.as_ptr(),
+            data: self.get_data_ptr(),
+            size: self.data.len(),
+            file_in_archive: self.archive_file.is_some(),
+            persistent_data: self.persistent_data,
+        }
+    }
+}
+
+impl Drop for LoadedContent {
+    fn drop(&mut self) {
+        if let Some(temp_file) = &self.temp_file {
+            let _ = fs::remove_file(temp_file);
+        }
+    }
+}
+
+#[doc = "Arquivo onde uma memoria da rom (SRAM, RTC...) e salva quando a rom e descarregada."]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MemoryFile {
+    #[doc = "Id usado em retro_get_memory_data."]
+    pub id: u32,
+    pub path: PathBuf,
+}
+
+#[doc = "retro_game_info_ext que pode ser guardado no [crate::core::CoreWrapper]."]
+#[repr(transparent)]
+#[derive(Debug)]
+pub struct GameInfoExt(pub retro_game_info_ext);
+
+// os ponteiros apontam para um LoadedContent que vive junto com o CoreWrapper
+// e so e modificado quando a rom e carregada ou descarregada
+unsafe impl Send for GameInfoExt {}
+unsafe impl Sync for GameInfoExt {}
+
+impl Default for GameInfoExt {
+    fn default() -> Self {
+        GameInfoExt(retro_game_info_ext {
+            full_path: null(),
+            archive_path: null(),
+            archive_file: null(),
+            dir: null(),
+            name: null(),
+            ext: null(),
+            meta: null(),
+            data: null(),
+            size: 0,
+            file_in_archive: false,
+            persistent_data: false,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test_content {
+    use crate::{
+        content::{ContentInfoOverride, LoadedContent},
+        retro_sys::retro_system_content_info_override,
+        tools::ffi_tools::{get_str_from_ptr, make_c_string},
+    };
+    use std::{fs, path::Path, ptr::null};
+
+    #[test]
+    fn content_info_override() {
+        let extensions = make_c_string("SFC|smc").unwrap();
+        let raw = [
+            retro_system_content_info_override {
+                extensions: extensions.as_ptr(),
+                need_fullpath: false,
+                persistent_data: true,
+            },
+            retro_system_content_info_override {
+                extensions: null(),
+                need_fullpath: false,
+                persistent_data: false,
+            },
+        ];
+
+        let overrides = unsafe { ContentInfoOverride::from_raw_list(raw.as_ptr()) };
+
+        assert_eq!(overrides.len(), 1);
+        assert!(
+            ContentInfoOverride::find(&overrides, "sfc")
+                .unwrap()
+                .persistent_data
+        );
+        assert!(ContentInfoOverride::find(&overrides, "SMC").is_some());
+        assert!(ContentInfoOverride::find(&overrides, "bin").is_none());
+    }
+
+    #[test]
+    fn loaded_content() {
+        fs::create_dir_all("retro_out_test/content").unwrap();
+        let path = Path::new("retro_out_test/content/game.SFC");
+        fs::write(path, [1, 2, 3, 4]).unwrap();
+
+        let content = LoadedContent::new(path, false, true).unwrap();
+        let info_ext = content.to_game_info_ext();
+
+        assert_eq!(get_str_from_ptr(info_ext.name), "game");
+        assert_eq!(get_str_from_ptr(info_ext.ext), "sfc");
+        assert_eq!(get_str_from_ptr(info_ext.dir), "retro_out_test/content");
+        assert_eq!(info_ext.size, 4);
+        assert!(info_ext.persistent_data);
+        assert!(!info_ext.file_in_archive);
+        assert!(info_ext.archive_path.is_null());
+
+        let content = LoadedContent::new(path, true, false).unwrap();
+        let info = content.to_game_info();
+
+        assert!(info.data.is_null());
+        assert_eq!(info.size, 0);
+    }
+
+    #[test]
+    fn archive_content() {
+        let archive = Path::new("retro_out_test/content/games.zip");
+        let temp_dir = Path::new("retro_out_test/content/temp");
+
+        let content = LoadedContent::from_archive(
+            archive,
+            "roms/Game.GBA",
+            vec![1, 2],
+            false,
+            false,
+            temp_dir,
+        )
+        .unwrap();
+        let info_ext = content.to_game_info_ext();
+
+        assert_eq!(
+            get_str_from_ptr(info_ext.full_path),
+            "retro_out_test/content/games.zip#roms/Game.GBA"
+        );
+        assert_eq!(get_str_from_ptr(info_ext.archive_file), "roms/Game.GBA");
+        assert_eq!(get_str_from_ptr(info_ext.name), "Game");
+        assert_eq!(get_str_from_ptr(info_ext.ext), "gba");
+        assert!(info_ext.file_in_archive);
+        assert_eq!(info_ext.size, 2);
+
+        let content = LoadedContent::from_archive(
+            archive,
+            "roms/Game.GBA",
+            vec![1, 2],
+            true,
+            false,
+            temp_dir,
+        )
+        .unwrap();
+        let temp_file = content.temp_file.clone().unwrap();
+
+        assert_eq!(fs::read(&temp_file).unwrap(), vec![1, 2]);
+        assert_eq!(content.get_identity().unwrap().size, 2);
+
+        drop(content);
+        assert!(!temp_file.exists());
+    }
+
+    #[test]
+    fn memory_content() {
+        let virtual_path = Path::new("pack/roms/Game.NES");
+        let temp_dir = Path::new("retro_out_test/content/memory");
+
+        let content =
+            LoadedContent::from_memory(virtual_path, vec![7, 8, 9], "pack", false, true, temp_dir)
+                .unwrap();
+        let info_ext = content.to_game_info_ext();
+
+        assert_eq!(get_str_from_ptr(info_ext.full_path), "pack/roms/Game.NES");
+        assert_eq!(get_str_from_ptr(info_ext.name), "Game");
+        assert_eq!(get_str_from_ptr(info_ext.ext), "nes");
+        assert_eq!(get_str_from_ptr(info_ext.meta), "pack");
+        assert_eq!(info_ext.size, 3);
+        assert!(content.temp_file.is_none());
+        assert_eq!(content.get_identity().unwrap().size, 3);
+
+        //o buffer e a rom inteira mesmo vazio, nada e lido do disco
+        let content =
+            LoadedContent::from_memory(virtual_path, Vec::new(), "", false, false, temp_dir)
+                .unwrap();
+        assert_eq!(content.get_identity().unwrap().size, 0);
+
+        let content =
+            LoadedContent::from_memory(virtual_path, vec![7, 8, 9], "", true, false, temp_dir)
+                .unwrap();
+        let temp_file = content.temp_file.clone().unwrap();
+
+        assert_eq!(
+            get_str_from_ptr(content.to_game_info().path),
+            temp_file.display().to_string()
+        );
+        assert_eq!(fs::read(&temp_file).unwrap(), vec![7, 8, 9]);
+
+        drop(content);
+        assert!(!temp_file.exists());
+    }
+}
@@ -0,0 +1,218 @@
+use crate::erro_handle::{ErroHandle, RetroLogLevel};
+use md5::{Digest, Md5};
+use sha1::Sha1;
+use std::{fs::File, io::Read, path::Path};
+
+//maior cabeçalho conhecido (Atari 7800)
+const MAX_HEADER_SIZE: usize = 128;
+const SMC_HEADER_SIZE: u64 = 512;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContentHeader {
+    None,
+    #[doc = "Cabeçalho iNES de 16 bytes das roms de NES."]
+    INes,
+    #[doc = "Cabeçalho fwNES de 16 bytes das imagens de Famicom Disk System."]
+    Fds,
+    #[doc = "Cabeçalho de 64 bytes das roms de Atari Lynx."]
+    Lynx,
+    #[doc = "Cabeçalho de 128 bytes das roms de Atari 7800."]
+    Atari7800,
+    #[doc = "Cabeçalho de 512 bytes adicionado pelos copiadores de SNES e PC Engine."]
+    Copier,
+}
+
+impl ContentHeader {
+    pub fn get_size(&self) -> usize {
+        match self {
+            ContentHeader::None => 0,
+            ContentHeader::INes | ContentHeader::Fds => 16,
+            ContentHeader::Lynx => 64,
+            ContentHeader::Atari7800 => 128,
+            ContentHeader::Copier => SMC_HEADER_SIZE as usize,
+        }
+    }
+
+    #[doc = "Identifica o cabeçalho pelos primeiros bytes, extensão e tamanho total do conteúdo."]
+    pub fn detect(start: &[u8], extension: &str, total_size: u64) -> ContentHeader {
+        if start.starts_with(b"NES\x1a") {
+            return ContentHeader::INes;
+        }
+
+        if start.starts_with(b"FDS\x1a") {
+            return ContentHeader::Fds;
+        }
+
+        if start.starts_with(b"LYNX\0") {
+            return ContentHeader::Lynx;
+        }
+
+        if start.get(1..10) == Some(b"ATARI7800".as_slice()) {
+            return ContentHeader::Atari7800;
+        }
+
+        let is_copier_extension = matches!(
+            extension.to_lowercase().as_str(),
+            "smc" | "sfc" | "swc" | "fig" | "pce"
+        );
+
+        if is_copier_extension && total_size % 1024 == SMC_HEADER_SIZE {
+            return ContentHeader::Copier;
+        }
+
+        ContentHeader::None
+    }
+}
+
+#[doc = "
+    # Identidade do conteúdo
+
+    Hashes do conteúdo sem o cabeçalho, os mesmos usados pelos bancos de dados do libretro
+    e pelo RetroAchievements. Disponível em [crate::core::CoreWrapper::content_identity]
+    depois de load_game.
+"]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContentIdentity {
+    pub crc32: u32,
+    #[doc = "MD5 em hexadecimal minúsculo."]
+    pub md5: String,
+    #[doc = "SHA1 em hexadecimal minúsculo."]
+    pub sha1: String,
+    #[doc = "Quantidade de bytes usados no hash, sem o cabeçalho."]
+    pub size: u64,
+    pub header: ContentHeader,
+}
+
+impl ContentIdentity {
+    pub fn from_path(path: &Path) -> Result<ContentIdentity, ErroHandle> {
+        let file = File::open(path).map_err(|e| identity_error(e.to_string()))?;
+        let total_size = file
+            .metadata()
+            .map_err(|e| identity_error(e.to_string()))?
+            .len();
+
+        let extension = path.extension().unwrap_or_default().to_string_lossy();
+
+        Self::from_reader(file, &extension, total_size)
+    }
+
+    pub fn from_bytes(data: &[u8], extension: &str) -> ContentIdentity {
+        //ler de um slice nunca falha
+        Self::from_reader(data, extension, data.len() as u64).unwrap()
+    }
+
+    #[doc = "Lê `reader` em blocos, o conteúdo nunca fica inteiro na memoria."]
+    pub fn from_reader<R: Read>(
+        mut reader: R,
+        extension: &str,
+        total_size: u64,
+    ) -> Result<ContentIdentity, ErroHandle> {
+        let mut start = Vec::with_capacity(MAX_HEADER_SIZE);
+        (&mut reader)
+            .take(MAX_HEADER_SIZE as u64)
+            .read_to_end(&mut start)
+            .map_err(|e| identity_error(e.to_string()))?;
+
+        let header = ContentHeader::detect(&start, extension, total_size);
+        let mut skip = header.get_size();
+
+        let mut crc32 = crc32fast::Hasher::new();
+        let mut md5 = Md5::new();
+        let mut sha1 = Sha1::new();
+        let mut size = 0u64;
+
+        let mut update = |data: &[u8]| {
+            crc32.update(data);
+            md5.update(data);
+            sha1.update(data);
+            size += data.len() as u64;
+        };
+
+        let start_skip = skip.min(start.len());
+        update(&start[start_skip..]);
+        skip -= start_skip;
+
+        let mut buf = [0u8; 64 * 1024];
+
+        loop {
+            let read = match reader.read(&mut buf) {
+                Ok(0) => break,
+                Ok(read) => read,
+                Err(e) => return Err(identity_error(e.to_string())),
+            };
+
+            //o cabeçalho de copiador e maior que o bloco inicial
+            let chunk_skip = skip.min(read);
+            update(&buf[chunk_skip..read]);
+            skip -= chunk_skip;
+        }
+
+        Ok(ContentIdentity {
+            crc32: crc32.finalize(),
+            md5: to_hex(&md5.finalize()),
+            sha1: to_hex(&sha1.finalize()),
+            size,
+            header,
+        })
+    }
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn identity_error(message: String) -> ErroHandle {
+    ErroHandle {
+        level: RetroLogLevel::RETRO_LOG_ERROR,
+        message,
+    }
+}
+
+#[cfg(test)]
+mod test_content_identity {
+    use crate::content_identity::{ContentHeader, ContentIdentity};
+    use std::{fs, path::Path};
+
+    #[test]
+    fn known_hashes() {
+        let identity = ContentIdentity::from_bytes(b"abc", "bin");
+
+        assert_eq!(identity.crc32, 0x352441c2);
+        assert_eq!(identity.md5, "900150983cd24fb0d6963f7d28e17f72");
+        assert_eq!(identity.sha1, "a9993e364706816aba3e25717850c26c9cd0d89d");
+        assert_eq!(identity.header, ContentHeader::None);
+    }
+
+    #[test]
+    fn skip_headers() {
+        let rom: Vec<u8> = (0..2048u32).map(|value| value as u8).collect();
+        let expected = ContentIdentity::from_bytes(&rom, "bin");
+
+        let mut ines = b"NES\x1a".to_vec();
+        ines.resize(16, 0);
+        ines.extend_from_slice(&rom);
+
+        let identity = ContentIdentity::from_bytes(&ines, "nes");
+        assert_eq!(identity.header, ContentHeader::INes);
+        assert_eq!(identity.md5, expected.md5);
+
+        let mut smc = vec![0u8; 512];
+        smc.extend_from_slice(&rom);
+
+        fs::create_dir_all("retro_out_test/content").unwrap();
+        let path = Path::new("retro_out_test/content/copier.smc");
+        fs::write(path, &smc).unwrap();
+
+        let identity = ContentIdentity::from_path(path).unwrap();
+        assert_eq!(identity.header, ContentHeader::Copier);
+        assert_eq!(identity.crc32, expected.crc32);
+        assert_eq!(identity.sha1, expected.sha1);
+        assert_eq!(identity.size, 2048);
+
+        //sem o tamanho de um cabeçalho de copiador nada e removido
+        assert_eq!(
+            ContentIdentity::from_bytes(&rom, "sfc").header,
+            ContentHeader::None
+        );
+    }
+}
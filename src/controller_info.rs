@@ -17,6 +17,18 @@ pub struct ControllerInfo {
     pub num_types: Mutex<u32>,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ControllerDevice {
+    pub id: u32,
+    pub desc: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConnectedController {
+    pub port: u32,
+    pub device: ControllerDevice,
+}
+
 impl ControllerInfo {
     pub fn from_raw(raw_ctr_info: retro_controller_info) -> ControllerInfo {
         let mut controller_info = ControllerInfo::default();
@@ -43,4 +55,68 @@ impl ControllerInfo {
 
         controller_info
     }
+
+    pub fn get_devices(&self) -> Vec<ControllerDevice> {
+        self.types
+            .iter()
+            .map(|ctr_type| ControllerDevice {
+                id: *ctr_type.id.lock().unwrap(),
+                desc: ctr_type.desc.lock().unwrap().clone(),
+            })
+            .collect()
+    }
+
+    pub fn find_by_id(&self, id: u32) -> Option<ControllerDevice> {
+        self.get_devices()
+            .into_iter()
+            .find(|device| device.id == id)
+    }
+
+    pub fn find_by_desc(&self, desc: &str) -> Option<ControllerDevice> {
+        self.get_devices()
+            .into_iter()
+            .find(|device| device.desc.eq_ignore_ascii_case(desc))
+    }
+}
+
+#[cfg(test)]
+mod test_controller_info {
+    use crate::{
+        binding::binding_libretro::{retro_controller_description, retro_controller_info},
+        constants::MAX_CORE_CONTROLLER_INFO_TYPES,
+        controller_info::{ControllerDevice, ControllerInfo},
+    };
+    use std::ptr::null;
+
+    #[test]
+    fn find_devices() {
+        let mut raw_types = [retro_controller_description {
+            desc: null(),
+            id: 0,
+        }; MAX_CORE_CONTROLLER_INFO_TYPES];
+
+        raw_types[0] = retro_controller_description {
+            desc: "SNES Joypad\0".as_ptr() as *const i8,
+            id: 1,
+        };
+        raw_types[1] = retro_controller_description {
+            desc: "SNES Mouse\0".as_ptr() as *const i8,
+            id: 2,
+        };
+
+        let controller_info = ControllerInfo::from_raw(retro_controller_info {
+            types: raw_types.as_ptr(),
+            num_types: 2,
+        });
+
+        let mouse = ControllerDevice {
+            id: 2,
+            desc: "SNES Mouse".to_string(),
+        };
+
+        assert_eq!(controller_info.get_devices().len(), 2);
+        assert_eq!(controller_info.find_by_id(2), Some(mouse.clone()));
+        assert_eq!(controller_info.find_by_desc("snes mouse"), Some(mouse));
+        assert_eq!(controller_info.find_by_id(5), None);
+    }
 }
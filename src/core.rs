@@ -1,20 +1,59 @@
-pub use crate::av_info::{AvInfo, Geometry, Timing, Video};
+pub use crate::av_info::{Audio, AudioBufferStatus, AvInfo, Geometry, Timing, Video};
 pub use crate::binding::binding_libretro::retro_language;
 pub use crate::binding::binding_libretro::retro_pixel_format;
-pub use crate::environment::RetroEnvCallbacks;
+use crate::binding::binding_libretro::{
+    retro_audio_callback, retro_frame_time_callback, retro_rumble_effect, RETRO_MEMORY_RTC,
+    RETRO_MEMORY_SAVE_RAM, RETRO_MEMORY_SYSTEM_RAM, RETRO_MEMORY_VIDEO_RAM,
+};
+use crate::constants::MAX_CORE_LEDS;
+pub use crate::content::{ContentInfoOverride, LoadedContent, MemoryFile};
+pub use crate::content_identity::{ContentHeader, ContentIdentity};
+pub use crate::controller_info::{ConnectedController, ControllerDevice};
+pub use crate::environment::{RetroEnvCallbacks, RetroOptionalCallbacks};
 use crate::erro_handle::{ErroHandle, RetroLogLevel};
 use crate::graphic_api::GraphicApi;
+pub use crate::memory::{Memory, MemoryDescriptor, MemoryRegion};
+pub use crate::message::{CoreMessage, MessageDuration};
+pub use crate::ram_search::{
+    RamSearch, SearchCandidate, SearchCompare, SearchOptions, SearchSize, SearchTarget,
+};
+pub use crate::rumble::RumbleState;
+pub use crate::sensor::{PortSensors, SensorInput, SensorType};
+pub use crate::speed::{FastForwardOverride, SpeedState};
 use crate::tools::game_tools::RomTools;
 use crate::{
-    binding::binding_libretro::LibretroRaw, environment, managers::option_manager::OptionManager,
-    paths::Paths, system::System,
+    binding::binding_libretro::LibretroRaw,
+    content::GameInfoExt,
+    environment,
+    managers::{
+        cheat_manager::{Cheat, CheatManager},
+        movie_manager::{Movie, MovieManager, MovieState},
+        option_manager::OptionManager,
+        remap_manager::RemapManager,
+    },
+    message::MessageQueue,
+    paths::Paths,
+    rumble::Rumble,
+    sensor::Sensors,
+    system::System,
+    vfs::{StdFsVfs, VfsBackend},
 };
-use std::path::PathBuf;
-use std::sync::{Arc, Mutex};
+use log::warn;
+use std::path::{Path, PathBuf};
+use std::ptr::null;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Instant;
 use uuid::Uuid;
 
 pub type CoreWrapperIns = Arc<CoreWrapper>;
 
+fn hardcore_error(message: &str) -> ErroHandle {
+    ErroHandle {
+        level: RetroLogLevel::RETRO_LOG_WARN,
+        message: message.to_string(),
+    }
+}
+
 pub struct CoreWrapper {
     /// # Retro_context_associated
     ///
@@ -22,16 +61,52 @@ pub struct CoreWrapper {
     /// [RetroContext] dentro das callbacks fornecidas por [environment],
     pub retro_ctx_associated: Uuid,
     pub rom_name: Mutex<String>,
+    pub content_identity: Mutex<Option<ContentIdentity>>,
+    #[doc = "Arquivo escolhido dentro do zip/7z, None se a rom nao veio de um arquivo compactado."]
+    pub archive_entry: Mutex<Option<String>>,
     pub initialized: Mutex<bool>,
     pub game_loaded: Mutex<bool>,
     pub support_no_game: Mutex<bool>,
     pub language: Mutex<retro_language>,
+    pub connected_controllers: Mutex<Vec<ConnectedController>>,
+    #[doc = "Estado de cada LED informado pelo núcleo, o índice e o numero do LED."]
+    pub leds: Mutex<Vec<i32>>,
+    #[doc = "Mensagens enviadas pelo núcleo que ainda nao foram lidas pelo frontend."]
+    pub messages: MessageQueue,
+    pub frame_time_callback: Mutex<Option<retro_frame_time_callback>>,
+    #[doc = "Se verdadeiro o núcleo sempre recebe o tempo de referencia, útil para gravações e testes."]
+    pub fixed_frame_time: Mutex<bool>,
+    pub last_frame_time: Mutex<Option<Instant>>,
+    pub audio_callback: Mutex<Option<retro_audio_callback>>,
+    #[doc = "Velocidade escolhida pelo frontend, use [CoreWrapper::get_speed] para saber a velocidade real."]
+    pub speed: Mutex<SpeedState>,
+    pub fastforward_override: Mutex<Option<FastForwardOverride>>,
+    pub content_overrides: RwLock<Vec<ContentInfoOverride>>,
+    #[doc = "Roms enviadas ao núcleo, seus buffers ficam vivos ate a rom ser descarregada."]
+    pub loaded_content: Mutex<Vec<LoadedContent>>,
+    pub(crate) game_info_ext: Mutex<Vec<GameInfoExt>>,
+    #[doc = "Memorias das roms carregadas com [CoreWrapper::load_game_special]."]
+    pub memory_files: Mutex<Vec<MemoryFile>>,
+    #[doc = "Id do subsystem usado em [CoreWrapper::load_game_special]."]
+    pub subsystem_id: Mutex<Option<u32>>,
     pub av_info: Arc<AvInfo>,
     pub system: System,
     pub paths: Paths,
     pub options: Arc<OptionManager>,
+    pub remaps: Arc<RemapManager>,
+    pub cheats: Arc<CheatManager>,
+    pub hardcore: Mutex<bool>,
+    pub movie: Arc<MovieManager>,
+    pub rumble: Rumble,
+    #[doc = "Acesso a memoria emulada, usa os mapas enviados em RETRO_ENVIRONMENT_SET_MEMORY_MAPS."]
+    pub memory: Memory,
+    #[doc = "Valores dos sensores que o frontend fornece ao núcleo."]
+    pub sensors: Sensors,
+    #[doc = "Backend usado pelo núcleo quando ele pede RETRO_ENVIRONMENT_GET_VFS_INTERFACE."]
+    pub vfs: RwLock<Arc<dyn VfsBackend>>,
     pub raw: Arc<LibretroRaw>,
     pub callbacks: RetroEnvCallbacks,
+    pub optional_callbacks: RwLock<RetroOptionalCallbacks>,
 }
 
 impl CoreWrapper {
@@ -51,6 +126,20 @@ impl CoreWrapper {
             system.info.library_name.read().unwrap().clone(),
         ));
 
+        let remaps = Arc::new(RemapManager::new(
+            &paths.opt,
+            system.info.library_name.read().unwrap().clone(),
+        ));
+        remaps.try_reload_remap(None);
+
+        let cheats = Arc::new(CheatManager::new(
+            &paths.cheats,
+            system.info.library_name.read().unwrap().clone(),
+        ));
+
+        //sem sandbox, veja [StdFsVfs::from_paths]
+        let vfs: Arc<dyn VfsBackend> = Arc::new(StdFsVfs::default());
+
         let core = Arc::new(CoreWrapper {
             raw: Arc::new(raw),
             initialized: Mutex::new(false),
@@ -58,10 +147,35 @@ impl CoreWrapper {
             support_no_game: Mutex::new(false),
             av_info: Arc::new(AvInfo::new(graphic_api)),
             rom_name: Mutex::new("".to_string()),
+            content_identity: Mutex::new(None),
+            archive_entry: Mutex::new(None),
+            connected_controllers: Mutex::new(Vec::new()),
+            leds: Mutex::new(Vec::new()),
+            messages: MessageQueue::default(),
+            frame_time_callback: Mutex::new(None),
+            fixed_frame_time: Mutex::new(false),
+            last_frame_time: Mutex::new(None),
+            audio_callback: Mutex::new(None),
+            speed: Mutex::new(SpeedState::Normal),
+            fastforward_override: Mutex::new(None),
+            content_overrides: RwLock::new(Vec::new()),
+            loaded_content: Mutex::new(Vec::new()),
+            game_info_ext: Mutex::new(Vec::new()),
+            memory_files: Mutex::new(Vec::new()),
+            subsystem_id: Mutex::new(None),
             system,
             paths,
             options,
+            remaps,
+            cheats,
+            hardcore: Mutex::new(false),
+            movie: Arc::new(MovieManager::default()),
+            rumble: Rumble::default(),
+            memory: Memory::default(),
+            sensors: Sensors::default(),
+            vfs: RwLock::new(vfs),
             callbacks,
+            optional_callbacks: RwLock::new(RetroOptionalCallbacks::default()),
             retro_ctx_associated,
             //TODO:precisa modificado de acordo com o idioma selecionado no sistema operacional!
             language: Mutex::new(retro_language::RETRO_LANGUAGE_PORTUGUESE_BRAZIL),
@@ -109,7 +223,7 @@ impl CoreWrapper {
         }
     }
 
-    pub fn load_game(&self, path: &str) -> Result<bool, ErroHandle> {
+    fn can_load_game(&self) -> Result<(), ErroHandle> {
         if *self.game_loaded.lock().unwrap() {
             return Err(ErroHandle {
                 level: RetroLogLevel::RETRO_LOG_WARN,
@@ -124,15 +238,258 @@ impl CoreWrapper {
             });
         }
 
-        let state = RomTools::create_game_info(self, path)?;
+        Ok(())
+    }
 
+    //`path` e usado para nomear os saves, remaps e movies quando nenhum conteúdo foi carregado
+    fn on_game_loaded(&self, state: bool, path: Option<&str>) -> Result<(), ErroHandle> {
         *self.game_loaded.lock().unwrap() = state;
-        *self.rom_name.lock().unwrap() = RomTools::get_rom_name(&PathBuf::from(path))?;
 
+        if !state {
+            return Ok(());
+        }
+
+        //roms extraídas de um zip/7z sao nomeadas pelo arquivo de dentro
+        let (rom_name, archive_entry, identity) = match (
+            self.loaded_content
+                .lock()
+                .unwrap()
+                .iter()
+                .find(|content| !content.is_empty()),
+            path,
+        ) {
+            (Some(content), _) => (
+                content.get_name(),
+                content
+                    .archive_file
+                    .as_ref()
+                    .map(|entry| entry.to_string_lossy().to_string()),
+                content.get_identity().map(Some),
+            ),
+            (None, Some(path)) => (
+                RomTools::get_rom_name(&PathBuf::from(path))?,
+                None,
+                ContentIdentity::from_path(Path::new(path)).map(Some),
+            ),
+            //sem conteúdo os arquivos sao nomeados pelo núcleo, igual ao RetroArch
+            (None, None) => (
+                self.system.info.library_name.read().unwrap().clone(),
+                None,
+                Ok(None),
+            ),
+        };
+
+        //o núcleo ja carregou a rom, sem a identidade so os movies deixam de ser verificados
+        let identity = identity.unwrap_or_else(|e| {
+            warn!("Nao foi possível identificar a rom: {}", e.message);
+            None
+        });
+
+        *self.rom_name.lock().unwrap() = rom_name;
+        *self.archive_entry.lock().unwrap() = archive_entry;
+        *self.content_identity.lock().unwrap() = identity;
+
+        self.remaps
+            .try_reload_remap(Some(&self.rom_name.lock().unwrap()));
+        self.cheats
+            .load_game_cheats(Some(&self.rom_name.lock().unwrap()));
+        self.on_core_game_ready();
+
+        Ok(())
+    }
+
+    //tudo que o núcleo esquece quando a rom e carregada de novo
+    fn on_core_game_ready(&self) {
+        self.reapply_controllers();
         self.av_info.update_av_info(&self.raw);
+        self.reapply_core_cheats();
+
+        unsafe {
+            self.memory.set_system_ram(
+                self.raw.retro_get_memory_data(RETRO_MEMORY_SYSTEM_RAM) as *mut u8,
+                self.raw.retro_get_memory_size(RETRO_MEMORY_SYSTEM_RAM),
+            );
+        }
+    }
+
+    //retro_reset e so um soft reset, o power-on descarrega e carrega a mesma rom de novo
+    fn power_cycle(&self) -> Result<(), ErroHandle> {
+        if !RomTools::reload_game(self)? {
+            //retro_unload_game ja foi chamado, o estado precisa ficar igual ao de unload_game
+            if self.cheats.game_file_path.read().unwrap().is_some() {
+                if let Err(e) = self.cheats.save_cheats() {
+                    warn!("{}", e.message);
+                }
+            }
+            self.cheats.load_game_cheats(None);
+            self.clear_game_state();
+
+            return Err(ErroHandle {
+                level: RetroLogLevel::RETRO_LOG_ERROR,
+                message: "O núcleo nao conseguiu carregar a rom de novo".to_string(),
+            });
+        }
+
+        self.on_core_game_ready();
+
+        Ok(())
+    }
+
+    //tudo que pertence a rom depois de retro_unload_game
+    fn clear_game_state(&self) {
+        *self.game_loaded.lock().unwrap() = false;
+        self.memory_files.lock().unwrap().clear();
+        *self.subsystem_id.lock().unwrap() = None;
+        self.memory.clear();
+        *self.content_identity.lock().unwrap() = None;
+        *self.archive_entry.lock().unwrap() = None;
+        *self.frame_time_callback.lock().unwrap() = None;
+        *self.last_frame_time.lock().unwrap() = None;
+        *self.audio_callback.lock().unwrap() = None;
+        *self.av_info.audio.buffer_status_callback.lock().unwrap() = None;
+        *self.av_info.audio.minimum_latency.lock().unwrap() = 0;
+        *self.fastforward_override.lock().unwrap() = None;
+        self.set_loaded_content(Vec::new());
+        self.vfs.read().unwrap().reset_allowed_dirs();
+        self.movie.stop();
+        self.stop_rumble();
+        self.turn_off_leds();
+    }
+
+    pub fn load_game(&self, path: &str) -> Result<bool, ErroHandle> {
+        self.can_load_game()?;
+
+        if let Some(rom_dir) = Path::new(path).parent() {
+            self.vfs.read().unwrap().allow_dir(rom_dir);
+        }
+
+        let state = RomTools::create_game_info(self, path, None, None)?;
+        self.on_game_loaded(state, Some(path))?;
+
+        Ok(state)
+    }
+
+    #[doc = "Carrega `entry` de dentro do zip ou 7z em `path`, [CoreWrapper::load_game] escolhe a primeira rom valida."]
+    pub fn load_game_from_archive(&self, path: &str, entry: &str) -> Result<bool, ErroHandle> {
+        self.can_load_game()?;
+
+        if let Some(rom_dir) = Path::new(path).parent() {
+            self.vfs.read().unwrap().allow_dir(rom_dir);
+        }
+
+        let state = RomTools::create_game_info(self, path, Some(entry), None)?;
+        self.on_game_loaded(state, Some(path))?;
+
         Ok(state)
     }
 
+    #[doc = "
+        Carrega uma rom a partir de um buffer, sem precisar de um arquivo no disco.
+
+        `virtual_path` nomeia a rom (saves, cheats, remaps...) e define a extensão enviada ao
+        núcleo, `meta` e repassado em retro_game_info. Se o núcleo precisar de um caminho o
+        buffer e escrito em `<save>/temp/<núcleo>` e o arquivo e apagado quando a rom e
+        descarregada.
+    "]
+    pub fn load_game_from_memory(
+        &self,
+        data: Vec<u8>,
+        virtual_path: &str,
+        meta: &str,
+    ) -> Result<bool, ErroHandle> {
+        self.can_load_game()?;
+
+        let state = RomTools::create_memory_game_info(self, data, virtual_path, meta)?;
+        self.on_game_loaded(state, Some(virtual_path))?;
+
+        Ok(state)
+    }
+
+    #[doc = "
+        Carrega a rom aplicando o patch IPS, BPS ou UPS em `patch_path`. [CoreWrapper::load_game]
+        aplica sozinho um patch com o mesmo nome da rom.
+    "]
+    pub fn load_game_with_patch(&self, path: &str, patch_path: &str) -> Result<bool, ErroHandle> {
+        self.can_load_game()?;
+
+        if let Some(rom_dir) = Path::new(path).parent() {
+            self.vfs.read().unwrap().allow_dir(rom_dir);
+        }
+
+        let state = RomTools::create_game_info(self, path, None, Some(patch_path))?;
+        self.on_game_loaded(state, Some(path))?;
+
+        Ok(state)
+    }
+
+    #[doc = "
+        Inicia o núcleo sem nenhuma rom, so funciona se o núcleo pediu
+        RETRO_ENVIRONMENT_SET_SUPPORT_NO_GAME (ex: 2048, Dinothawr). Depois disso run, reset
+        e os save states funcionam normalmente, os arquivos usam o nome do núcleo.
+    "]
+    pub fn start_without_content(&self) -> Result<bool, ErroHandle> {
+        self.can_load_game()?;
+
+        if !*self.support_no_game.lock().unwrap() {
+            return Err(ErroHandle {
+                level: RetroLogLevel::RETRO_LOG_ERROR,
+                message: "O núcleo nao pode ser iniciado sem uma rom".to_string(),
+            });
+        }
+
+        self.set_loaded_content(Vec::new());
+
+        let state = unsafe { self.raw.retro_load_game(null()) };
+        self.on_game_loaded(state, None)?;
+
+        Ok(state)
+    }
+
+    #[doc = "
+        # Carregar um subsystem
+
+        Usado por conteúdos que precisam de mais de uma rom, ex: Super Game Boy ou Sufami Turbo.
+        `paths` deve seguir a ordem de [crate::system::SubSystemInfo::roms], roms opcionais
+        podem ser enviadas como \"\". As memorias de cada rom sao salvas em arquivos separados
+        dentro da pasta save quando a rom e descarregada.
+    "]
+    pub fn load_game_special(
+        &self,
+        subsystem_ident: &str,
+        paths: &[&str],
+    ) -> Result<bool, ErroHandle> {
+        self.can_load_game()?;
+
+        let main_path = match paths.iter().find(|path| !path.is_empty()) {
+            Some(path) => *path,
+            None => {
+                return Err(ErroHandle {
+                    level: RetroLogLevel::RETRO_LOG_ERROR,
+                    message: "Nenhuma rom foi enviada para o subsystem".to_string(),
+                })
+            }
+        };
+
+        for path in paths.iter().filter(|path| !path.is_empty()) {
+            if let Some(rom_dir) = Path::new(path).parent() {
+                self.vfs.read().unwrap().allow_dir(rom_dir);
+            }
+        }
+
+        let state = RomTools::create_subsystem_game_info(self, subsystem_ident, paths)?;
+        self.on_game_loaded(state, Some(main_path))?;
+
+        Ok(state)
+    }
+
+    pub(crate) fn set_loaded_content(&self, content: Vec<LoadedContent>) {
+        *self.game_info_ext.lock().unwrap() = content
+            .iter()
+            .map(|content| GameInfoExt(content.to_game_info_ext()))
+            .collect();
+        *self.loaded_content.lock().unwrap() = content;
+    }
+
     pub fn reset(&self) -> Result<(), ErroHandle> {
         if !*self.initialized.lock().unwrap() {
             return Err(ErroHandle {
@@ -148,9 +505,13 @@ impl CoreWrapper {
             });
         }
 
+        self.check_no_movie("Nao e possível reiniciar durante um movie")?;
+
         unsafe {
             self.raw.retro_reset();
         }
+        self.reapply_controllers();
+        self.reapply_core_cheats();
 
         Ok(())
     }
@@ -170,11 +531,169 @@ impl CoreWrapper {
             });
         }
 
+        self.call_frame_time_callback();
+        self.av_info.audio.call_buffer_status_callback();
+
+        if !*self.hardcore.lock().unwrap() {
+            self.cheats.apply_ram_cheats(&self.memory);
+        }
+
         unsafe { self.raw.retro_run() }
 
+        self.call_achievements_callback();
+
         Ok(())
     }
 
+    fn call_achievements_callback(&self) {
+        let achievements_callback = match self.get_optional_callbacks().achievements_callback {
+            Some(callback) => callback,
+            None => return,
+        };
+
+        let regions: Vec<MemoryRegion> = [
+            RETRO_MEMORY_SYSTEM_RAM,
+            RETRO_MEMORY_SAVE_RAM,
+            RETRO_MEMORY_VIDEO_RAM,
+            RETRO_MEMORY_RTC,
+        ]
+        .into_iter()
+        .filter_map(|id| unsafe {
+            let data = self.raw.retro_get_memory_data(id);
+            let size = self.raw.retro_get_memory_size(id);
+
+            if data.is_null() || size == 0 {
+                None
+            } else {
+                Some(MemoryRegion {
+                    id,
+                    data: std::slice::from_raw_parts(data as *const u8, size),
+                })
+            }
+        })
+        .collect();
+
+        achievements_callback(&regions);
+    }
+
+    pub fn set_optional_callbacks(&self, callbacks: RetroOptionalCallbacks) {
+        *self.optional_callbacks.write().unwrap() = callbacks;
+    }
+
+    pub fn get_optional_callbacks(&self) -> RetroOptionalCallbacks {
+        *self.optional_callbacks.read().unwrap()
+    }
+
+    #[doc = "
+        No modo hardcore os cheats sao desativados, [CoreWrapper::load_state] e os movies
+        que começam de um state sao recusados, como exigido pelos serviços de conquistas.
+    "]
+    pub fn set_hardcore_mode(&self, enabled: bool) {
+        *self.hardcore.lock().unwrap() = enabled;
+        self.reapply_core_cheats();
+    }
+
+    pub fn is_hardcore_mode(&self) -> bool {
+        *self.hardcore.lock().unwrap()
+    }
+
+    //o núcleo precisa saber quanto tempo passou desde o ultimo retro_run
+    fn call_frame_time_callback(&self) {
+        let frame_time = match *self.frame_time_callback.lock().unwrap() {
+            Some(frame_time) => frame_time,
+            None => return,
+        };
+
+        let now = Instant::now();
+        let last = self.last_frame_time.lock().unwrap().replace(now);
+
+        let usec = match last {
+            Some(last) if !*self.fixed_frame_time.lock().unwrap() => {
+                now.duration_since(last).as_micros() as i64
+            }
+            _ => frame_time.reference,
+        };
+
+        if let Some(callback) = frame_time.callback {
+            unsafe { callback(usec) }
+        }
+    }
+
+    #[doc = "
+        Informa o estado do buffer de áudio do frontend, o núcleo recebe esse valor no próximo run.
+        `occupancy` e a porcentagem do buffer que esta preenchida (0 a 100).
+    "]
+    pub fn set_audio_buffer_status(&self, active: bool, occupancy: u32, underrun_likely: bool) {
+        *self.av_info.audio.buffer_status.lock().unwrap() = AudioBufferStatus {
+            active,
+            occupancy: occupancy.min(100),
+            underrun_likely,
+        };
+    }
+
+    #[doc = "Latência mínima em milissegundos pedida pelo núcleo, use para dimensionar o buffer de áudio."]
+    pub fn get_minimum_audio_latency(&self) -> u32 {
+        *self.av_info.audio.minimum_latency.lock().unwrap()
+    }
+
+    pub fn set_speed(&self, speed: SpeedState) {
+        *self.speed.lock().unwrap() = speed;
+    }
+
+    #[doc = "Velocidade escolhida pelo frontend depois de aplicar o override pedido pelo núcleo."]
+    pub fn get_speed(&self) -> SpeedState {
+        let speed = *self.speed.lock().unwrap();
+
+        match *self.fastforward_override.lock().unwrap() {
+            Some(ff_override) => ff_override.apply(speed),
+            None => speed,
+        }
+    }
+
+    pub fn set_fixed_frame_time(&self, fixed: bool) {
+        *self.fixed_frame_time.lock().unwrap() = fixed;
+    }
+
+    #[doc = "Verdadeiro se o núcleo espera que o frontend peça o áudio com [CoreWrapper::run_audio_callback]."]
+    pub fn has_audio_callback(&self) -> bool {
+        self.audio_callback.lock().unwrap().is_some()
+    }
+
+    #[doc = "
+        Pede ao núcleo que escreva mais amostras de áudio, elas chegam em audio_sample_batch_callback.
+        Deve ser chamada pela thread de áudio do frontend sempre que o buffer precisar de mais dados.
+    "]
+    pub fn run_audio_callback(&self) -> Result<(), ErroHandle> {
+        let audio_callback = *self.audio_callback.lock().unwrap();
+
+        match audio_callback.and_then(|audio_callback| audio_callback.callback) {
+            Some(callback) => {
+                unsafe { callback() }
+                Ok(())
+            }
+            None => Err(ErroHandle {
+                level: RetroLogLevel::RETRO_LOG_WARN,
+                message: "O núcleo nao registrou uma audio callback".to_string(),
+            }),
+        }
+    }
+
+    #[doc = "Avisa o núcleo se o frontend esta ou nao tocando o áudio."]
+    pub fn set_audio_callback_state(&self, enabled: bool) -> Result<(), ErroHandle> {
+        let audio_callback = *self.audio_callback.lock().unwrap();
+
+        match audio_callback.and_then(|audio_callback| audio_callback.set_state) {
+            Some(set_state) => {
+                unsafe { set_state(enabled) }
+                Ok(())
+            }
+            None => Err(ErroHandle {
+                level: RetroLogLevel::RETRO_LOG_WARN,
+                message: "O núcleo nao registrou uma audio callback".to_string(),
+            }),
+        }
+    }
+
     pub fn de_init(&self) -> Result<(), ErroHandle> {
         //Se uma *rom* estive carrega ela deve ser descarregada primeiro
         match self.unload_game() {
@@ -202,6 +721,39 @@ impl CoreWrapper {
         Ok(())
     }
 
+    #[doc = "Remove e retorna as mensagens enviadas pelo núcleo desde a ultima chamada."]
+    pub fn drain_messages(&self) -> Vec<CoreMessage> {
+        self.messages.drain()
+    }
+
+    pub fn push_message(&self, message: CoreMessage) {
+        if let Some(message_callback) = self.get_optional_callbacks().message_callback {
+            message_callback(&message);
+        }
+
+        self.messages.push(message);
+    }
+
+    #[doc = "
+        Troca o backend de arquivos usado pelo núcleo, arquivos que ja estão abertos nao sao afetados.
+        Use `StdFsVfs::from_paths(&core.paths)` para limitar o núcleo as pastas de [Paths].
+    "]
+    pub fn set_vfs(&self, backend: Arc<dyn VfsBackend>) {
+        *self.vfs.write().unwrap() = backend;
+    }
+
+    #[doc = "Lista os dispositivos que o núcleo oferece para a porta escolhida."]
+    pub fn get_port_devices(&self, port: u32) -> Result<Vec<ControllerDevice>, ErroHandle> {
+        match self.system.ports.read().unwrap().get(port as usize) {
+            Some(controller_info) => Ok(controller_info.get_devices()),
+            None => Err(ErroHandle {
+                level: RetroLogLevel::RETRO_LOG_WARN,
+                message: "O núcleo nao oferece nenhum dispositivo para a porta ".to_string()
+                    + &port.to_string(),
+            }),
+        }
+    }
+
     pub fn connect_controller(&self, port: u32, controller: u32) -> Result<(), ErroHandle> {
         if !*self.initialized.lock().unwrap() {
             return Err(ErroHandle {
@@ -211,13 +763,172 @@ impl CoreWrapper {
             });
         }
 
+        let ports = self.system.ports.read().unwrap();
+
+        //se o núcleo nao enviou RETRO_ENVIRONMENT_SET_CONTROLLER_INFO nao ha como validar o id
+        let device = if ports.is_empty() {
+            ControllerDevice {
+                id: controller,
+                desc: "".to_string(),
+            }
+        } else {
+            let device = ports
+                .get(port as usize)
+                .and_then(|controller_info| controller_info.find_by_id(controller));
+
+            match device {
+                Some(device) => device,
+                None => {
+                    return Err(ErroHandle {
+                        level: RetroLogLevel::RETRO_LOG_ERROR,
+                        message: "O dispositivo ".to_string()
+                            + &controller.to_string()
+                            + " nao e oferecido pelo núcleo na porta "
+                            + &port.to_string(),
+                    })
+                }
+            }
+        };
+        drop(ports);
+
         unsafe {
             self.raw.retro_set_controller_port_device(port, controller);
         }
 
+        let mut connected_controllers = self.connected_controllers.lock().unwrap();
+        connected_controllers.retain(|ctr| ctr.port != port);
+        connected_controllers.push(ConnectedController { port, device });
+
         Ok(())
     }
 
+    pub fn connect_controller_by_desc(&self, port: u32, desc: &str) -> Result<(), ErroHandle> {
+        let device = self
+            .system
+            .ports
+            .read()
+            .unwrap()
+            .get(port as usize)
+            .and_then(|controller_info| controller_info.find_by_desc(desc));
+
+        match device {
+            Some(device) => self.connect_controller(port, device.id),
+            None => Err(ErroHandle {
+                level: RetroLogLevel::RETRO_LOG_ERROR,
+                message: "O dispositivo \"".to_string()
+                    + desc
+                    + "\" nao e oferecido pelo núcleo na porta "
+                    + &port.to_string(),
+            }),
+        }
+    }
+
+    //o núcleo pode esquecer os dispositivos escolhidos depois de um load_game ou reset
+    fn reapply_controllers(&self) {
+        for ctr in &*self.connected_controllers.lock().unwrap() {
+            unsafe {
+                self.raw
+                    .retro_set_controller_port_device(ctr.port, ctr.device.id);
+            }
+        }
+    }
+
+    #[doc = "Força de vibração que deve ser aplicada no controle da porta escolhida."]
+    pub fn get_rumble_state(&self, port: u32) -> RumbleState {
+        self.rumble.get_scaled_state(port)
+    }
+
+    pub fn set_rumble_enabled(&self, enabled: bool) {
+        *self.rumble.enabled.write().unwrap() = enabled;
+        self.notify_rumble();
+    }
+
+    #[doc = "Escala a força pedida pelo núcleo, `intensity` vai de 0.0 ate 1.0."]
+    pub fn set_rumble_intensity(&self, intensity: f32) {
+        self.rumble.set_intensity(intensity);
+        self.notify_rumble();
+    }
+
+    //reenvia ao frontend o estado de todas as portas depois de mudar enabled ou intensity
+    fn notify_rumble(&self) {
+        let num_ports = self.rumble.ports.read().unwrap().len() as u32;
+
+        for port in 0..num_ports {
+            let state = self.rumble.get_scaled_state(port);
+
+            (self.callbacks.rumble_callback)(
+                port,
+                retro_rumble_effect::RETRO_RUMBLE_STRONG,
+                state.strong,
+            );
+            (self.callbacks.rumble_callback)(
+                port,
+                retro_rumble_effect::RETRO_RUMBLE_WEAK,
+                state.weak,
+            );
+        }
+    }
+
+    //o núcleo nao desliga a vibração ao descarregar a rom
+    fn stop_rumble(&self) {
+        let num_ports = self.rumble.ports.read().unwrap().len() as u32;
+
+        for port in 0..num_ports {
+            (self.callbacks.rumble_callback)(port, retro_rumble_effect::RETRO_RUMBLE_STRONG, 0);
+            (self.callbacks.rumble_callback)(port, retro_rumble_effect::RETRO_RUMBLE_WEAK, 0);
+        }
+
+        self.rumble.clear();
+    }
+
+    #[doc = "Retorna 0 para LEDs que o núcleo nunca acendeu."]
+    pub fn get_led_state(&self, led: i32) -> i32 {
+        if led < 0 {
+            return 0;
+        }
+
+        self.leds
+            .lock()
+            .unwrap()
+            .get(led as usize)
+            .copied()
+            .unwrap_or(0)
+    }
+
+    #[doc = "Retorna verdadeiro se o estado do LED mudou, LEDs acima de MAX_CORE_LEDS sao ignorados."]
+    pub fn set_led_state(&self, led: i32, state: i32) -> bool {
+        if led < 0 || led as usize >= MAX_CORE_LEDS {
+            return false;
+        }
+
+        let mut leds = self.leds.lock().unwrap();
+
+        if leds.len() <= led as usize {
+            leds.resize(led as usize + 1, 0);
+        }
+
+        let changed = leds[led as usize] != state;
+        leds[led as usize] = state;
+
+        changed
+    }
+
+    //apaga os LEDs que ficaram acesos quando a rom foi descarregada
+    fn turn_off_leds(&self) {
+        let leds = std::mem::take(&mut *self.leds.lock().unwrap());
+
+        let led_callback = match self.get_optional_callbacks().led_callback {
+            Some(callback) => callback,
+            None => return,
+        };
+
+        for (led, state) in leds.iter().enumerate() {
+            if *state != 0 {
+                led_callback(led as i32, 0);
+            }
+        }
+    }
+
     pub fn unload_game(&self) -> Result<(), ErroHandle> {
         if !*self.game_loaded.lock().unwrap() {
             return Err(ErroHandle {
@@ -233,12 +944,21 @@ impl CoreWrapper {
             });
         }
 
+        //as memorias precisam ser lidas antes do núcleo liberar a rom
+        let mut save_result = RomTools::save_memory_files(self);
+
+        //um arquivo com `cheats = 0` e valido e apaga os cheats removidos
+        if self.cheats.game_file_path.read().unwrap().is_some() {
+            save_result = save_result.and(self.cheats.save_cheats());
+        }
+        self.cheats.load_game_cheats(None);
+
         unsafe {
             self.raw.retro_unload_game();
         }
-        *self.game_loaded.lock().unwrap() = false;
+        self.clear_game_state();
 
-        Ok(())
+        save_result
     }
 
     pub fn save_state(&self, slot: usize) -> Result<(), ErroHandle> {
@@ -276,7 +996,217 @@ impl CoreWrapper {
             });
         }
 
+        if *self.hardcore.lock().unwrap() {
+            return Err(hardcore_error(
+                "Nao e possível carregar um state no modo hardcore",
+            ));
+        }
+
+        self.check_no_movie("Nao e possível carregar um state durante um movie")?;
+
         RomTools::load_save_state(self, slot)?;
+        self.reapply_core_cheats();
+
+        Ok(())
+    }
+
+    #[doc = "
+        Copia a memoria `id` do núcleo (RETRO_MEMORY_SYSTEM_RAM, RETRO_MEMORY_SAVE_RAM...),
+        usada junto com [RamSearch].
+    "]
+    pub fn get_memory_data(&self, id: u32) -> Result<Vec<u8>, ErroHandle> {
+        if !*self.game_loaded.lock().unwrap() {
+            return Err(ErroHandle {
+                level: RetroLogLevel::RETRO_LOG_WARN,
+                message: "Uma rom precisa ser carregada primeiro".to_string(),
+            });
+        }
+
+        unsafe {
+            let data = self.raw.retro_get_memory_data(id);
+            let size = self.raw.retro_get_memory_size(id);
+
+            if data.is_null() || size == 0 {
+                return Err(ErroHandle {
+                    level: RetroLogLevel::RETRO_LOG_WARN,
+                    message: "O núcleo nao expõe essa memoria".to_string(),
+                });
+            }
+
+            Ok(std::slice::from_raw_parts(data as *const u8, size).to_vec())
+        }
+    }
+
+    #[doc = "Retorna o estado atual serializado pelo núcleo, útil para comparar duas execuções."]
+    pub fn get_state_data(&self) -> Result<Vec<u8>, ErroHandle> {
+        if !*self.game_loaded.lock().unwrap() {
+            return Err(ErroHandle {
+                level: RetroLogLevel::RETRO_LOG_WARN,
+                message: "Uma rom precisa ser carregada primeiro".to_string(),
+            });
+        }
+
+        RomTools::get_state_data(self)
+    }
+
+    #[doc = "
+        # Gravar um movie
+
+        Se `from_power_on` for verdadeiro a rom e carregada de novo antes da gravação,
+        se nao o estado atual e embutido no movie.
+    "]
+    pub fn start_movie_recording(&self, from_power_on: bool) -> Result<(), ErroHandle> {
+        if !*self.game_loaded.lock().unwrap() {
+            return Err(ErroHandle {
+                level: RetroLogLevel::RETRO_LOG_WARN,
+                message: "Uma rom precisa ser carregada primeiro".to_string(),
+            });
+        }
+
+        let save_state = if from_power_on {
+            self.power_cycle()?;
+            None
+        } else {
+            Some(RomTools::get_state_data(self)?)
+        };
+
+        self.movie.start_recording(Movie {
+            core_name: self.system.info.library_name.read().unwrap().clone(),
+            core_version: self.system.info.library_version.read().unwrap().clone(),
+            rom_crc32: self.get_content_crc32(),
+            save_state,
+            frames: Vec::new(),
+        });
+
+        Ok(())
+    }
+
+    //0 quando a rom nao pode ser identificada ou nenhum conteúdo foi carregado
+    fn get_content_crc32(&self) -> u32 {
+        self.content_identity
+            .lock()
+            .unwrap()
+            .as_ref()
+            .map_or(0, |identity| identity.crc32)
+    }
+
+    pub fn stop_movie_recording(&self, path: &str) -> Result<(), ErroHandle> {
+        if self.movie.get_state() != MovieState::Recording {
+            return Err(ErroHandle {
+                level: RetroLogLevel::RETRO_LOG_WARN,
+                message: "Nenhum movie esta sendo gravado no momento".to_string(),
+            });
+        }
+
+        self.movie.stop().write_to_file(&PathBuf::from(path))
+    }
+
+    pub fn play_movie(&self, path: &str) -> Result<(), ErroHandle> {
+        if !*self.game_loaded.lock().unwrap() {
+            return Err(ErroHandle {
+                level: RetroLogLevel::RETRO_LOG_WARN,
+                message: "Uma rom precisa ser carregada primeiro".to_string(),
+            });
+        }
+
+        let mut movie = Movie::read_from_file(&PathBuf::from(path))?;
+
+        if movie.core_name != *self.system.info.library_name.read().unwrap()
+            || movie.core_version != *self.system.info.library_version.read().unwrap()
+        {
+            return Err(ErroHandle {
+                level: RetroLogLevel::RETRO_LOG_ERROR,
+                message: "O movie foi gravado com outro núcleo: ".to_string()
+                    + &movie.core_name
+                    + " "
+                    + &movie.core_version,
+            });
+        }
+
+        if movie.rom_crc32 != self.get_content_crc32() {
+            return Err(ErroHandle {
+                level: RetroLogLevel::RETRO_LOG_ERROR,
+                message: "O movie foi gravado com outra rom".to_string(),
+            });
+        }
+
+        //o state embutido seria uma forma de contornar a recusa de load_state
+        if movie.save_state.is_some() && *self.hardcore.lock().unwrap() {
+            return Err(hardcore_error(
+                "Nao e possível reproduzir um movie com state no modo hardcore",
+            ));
+        }
+
+        match &mut movie.save_state {
+            Some(state) => RomTools::set_state_data(self, state)?,
+            None => self.power_cycle()?,
+        }
+
+        self.movie.start_playback(movie);
+
+        Ok(())
+    }
+
+    //o movie so guarda os inputs, um reset ou state no meio dele quebraria a reprodução
+    fn check_no_movie(&self, message: &str) -> Result<(), ErroHandle> {
+        if self.movie.get_state() != MovieState::Idle {
+            return Err(ErroHandle {
+                level: RetroLogLevel::RETRO_LOG_WARN,
+                message: message.to_string(),
+            });
+        }
+
+        Ok(())
+    }
+
+    pub fn stop_movie(&self) {
+        self.movie.stop();
+    }
+
+    //os cheats do núcleo so podem ser enviados com uma rom carregada
+    fn reapply_core_cheats(&self) {
+        if !*self.game_loaded.lock().unwrap() {
+            return;
+        }
+
+        if *self.hardcore.lock().unwrap() {
+            unsafe { self.raw.retro_cheat_reset() };
+        } else {
+            self.cheats.apply_core_cheats(&self.raw);
+        }
+    }
+
+    pub fn add_cheat(&self, cheat: Cheat) -> usize {
+        let index = self.cheats.add_cheat(cheat);
+        self.reapply_core_cheats();
+
+        index
+    }
+
+    pub fn remove_cheat(&self, index: usize) -> Result<Cheat, ErroHandle> {
+        let cheat = self.cheats.remove_cheat(index)?;
+        self.reapply_core_cheats();
+
+        Ok(cheat)
+    }
+
+    pub fn set_cheat_enabled(&self, index: usize, enabled: bool) -> Result<(), ErroHandle> {
+        if enabled && *self.hardcore.lock().unwrap() {
+            return Err(hardcore_error(
+                "Cheats nao podem ser ativados no modo hardcore",
+            ));
+        }
+
+        self.cheats.set_enabled(index, enabled)?;
+        self.reapply_core_cheats();
+
+        Ok(())
+    }
+
+    #[doc = "Substitui os cheats do jogo atual pelos de um arquivo .cht do RetroArch."]
+    pub fn load_cheat_file(&self, path: &str) -> Result<(), ErroHandle> {
+        self.cheats.load_file(Path::new(path))?;
+        self.reapply_core_cheats();
 
         Ok(())
     }
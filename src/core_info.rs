@@ -0,0 +1,323 @@
+use crate::erro_handle::{ErroHandle, RetroLogLevel};
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+const INFO_EXTENSION_FILE: &str = "info";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FirmwareInfo {
+    #[doc = "Caminho relativo a pasta system."]
+    pub path: String,
+    pub desc: String,
+    #[doc = "Se verdadeiro o núcleo funciona sem esse arquivo."]
+    pub optional: bool,
+    #[doc = "MD5 em hexadecimal minúsculo, lido das notas quando o .info nao informa."]
+    pub md5: Option<String>,
+}
+
+#[doc = "
+    # Informações do núcleo
+
+    Conteúdo de um arquivo .info do libretro, lido sem precisar carregar a biblioteca do núcleo.
+"]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CoreInfo {
+    #[doc = "Nome do arquivo sem a extensão, ex: snes9x_libretro."]
+    pub name: String,
+    pub display_name: String,
+    pub core_name: String,
+    pub system_name: String,
+    pub system_id: String,
+    pub manufacturer: String,
+    pub authors: Vec<String>,
+    pub license: Vec<String>,
+    #[doc = "Extensões em minúsculo e sem o ponto."]
+    pub supported_extensions: Vec<String>,
+    pub firmware: Vec<FirmwareInfo>,
+    pub database: Vec<String>,
+    pub savestate: bool,
+    pub notes: Vec<String>,
+}
+
+impl CoreInfo {
+    pub fn from_file(path: &Path) -> Result<CoreInfo, ErroHandle> {
+        let buf = fs::read_to_string(path).map_err(|e| ErroHandle {
+            level: RetroLogLevel::RETRO_LOG_ERROR,
+            message: "Nao foi possível ler ".to_string()
+                + &path.display().to_string()
+                + ": "
+                + &e.to_string(),
+        })?;
+
+        let name = path.file_stem().unwrap_or_default().to_string_lossy();
+
+        Ok(CoreInfo::parse(&name, &buf))
+    }
+
+    pub fn parse(name: &str, buf: &str) -> CoreInfo {
+        let values: HashMap<&str, &str> = buf
+            .lines()
+            .filter(|line| !line.trim_start().starts_with('#'))
+            .filter_map(|line| {
+                let (key, value) = line.split_once('=')?;
+                Some((key.trim(), value.trim().trim_matches('"')))
+            })
+            .collect();
+
+        let get = |key: &str| values.get(key).copied().unwrap_or_default().to_string();
+        let get_list = |key: &str| {
+            values
+                .get(key)
+                .map(|value| split_list(value))
+                .unwrap_or_default()
+        };
+
+        let notes = get_list("notes");
+
+        let firmware_count: usize = values
+            .get("firmware_count")
+            .and_then(|count| count.parse().ok())
+            .unwrap_or(0);
+
+        let firmware = (0..firmware_count)
+            .map(|index| {
+                let get = |field: &str| get(&format!("firmware{index}_{field}"));
+                let path = get("path");
+
+                let md5 = match get("md5") {
+                    md5 if md5.is_empty() => find_md5_in_notes(&notes, &path),
+                    md5 => Some(md5.to_lowercase()),
+                };
+
+                FirmwareInfo {
+                    desc: get("desc"),
+                    optional: get("opt") == "true",
+                    md5,
+                    path,
+                }
+            })
+            .collect();
+
+        CoreInfo {
+            name: name.to_string(),
+            display_name: get("display_name"),
+            core_name: get("corename"),
+            system_name: get("systemname"),
+            system_id: get("systemid"),
+            manufacturer: get("manufacturer"),
+            authors: get_list("authors"),
+            license: get_list("license"),
+            supported_extensions: get_list("supported_extensions")
+                .iter()
+                .map(|ext| ext.to_lowercase())
+                .collect(),
+            firmware,
+            database: get_list("database"),
+            savestate: get("savestate") == "true",
+            notes,
+        }
+    }
+
+    pub fn supports_extension(&self, extension: &str) -> bool {
+        let extension = extension.trim_start_matches('.').to_lowercase();
+
+        self.supported_extensions.contains(&extension)
+    }
+
+    #[doc = "Firmwares obrigatórios que nao existem em `system_dir`."]
+    pub fn get_missing_firmware(&self, system_dir: &Path) -> Vec<&FirmwareInfo> {
+        self.firmware
+            .iter()
+            .filter(|firmware| !firmware.optional && !system_dir.join(&firmware.path).exists())
+            .collect()
+    }
+}
+
+fn split_list(value: &str) -> Vec<String> {
+    value
+        .split('|')
+        .map(|item| item.trim())
+        .filter(|item| !item.is_empty())
+        .map(|item| item.to_string())
+        .collect()
+}
+
+// as notas dos .info oficiais trazem os hashes assim:
+// (!) scph5501.bin (md5): 490f666e1afb15b7362b406ed1cea246
+fn find_md5_in_notes(notes: &[String], firmware_path: &str) -> Option<String> {
+    let file_name = Path::new(firmware_path)
+        .file_name()?
+        .to_string_lossy()
+        .to_string();
+
+    notes.iter().find_map(|note| {
+        let (file, md5) = note.trim_start_matches("(!)").split_once("(md5):")?;
+
+        if file.trim() == file_name {
+            Some(md5.trim().to_lowercase())
+        } else {
+            None
+        }
+    })
+}
+
+#[doc = "
+    # Banco de dados dos núcleos
+
+    Reúne os .info de uma pasta para descobrir quais núcleos abrem uma rom ou um sistema.
+"]
+#[derive(Debug, Clone, Default)]
+pub struct CoreInfoIndex {
+    pub cores: Vec<CoreInfo>,
+}
+
+impl CoreInfoIndex {
+    #[doc = "Lê todos os arquivos .info de `dir`, arquivos que nao podem ser lidos sao ignorados."]
+    pub fn from_dir(dir: &Path) -> Result<CoreInfoIndex, ErroHandle> {
+        let entries = fs::read_dir(dir).map_err(|e| ErroHandle {
+            level: RetroLogLevel::RETRO_LOG_ERROR,
+            message: "Nao foi possível ler a pasta ".to_string()
+                + &dir.display().to_string()
+                + ": "
+                + &e.to_string(),
+        })?;
+
+        let mut paths: Vec<PathBuf> = entries
+            .filter_map(|entry| Some(entry.ok()?.path()))
+            .filter(|path| {
+                path.extension()
+                    .is_some_and(|ext| ext.eq_ignore_ascii_case(INFO_EXTENSION_FILE))
+            })
+            .collect();
+        paths.sort();
+
+        Ok(CoreInfoIndex {
+            cores: paths
+                .iter()
+                .filter_map(|path| CoreInfo::from_file(path).ok())
+                .collect(),
+        })
+    }
+
+    pub fn add(&mut self, info: CoreInfo) {
+        self.cores.push(info);
+    }
+
+    #[doc = "Procura pelo nome do arquivo, ex: snes9x_libretro."]
+    pub fn find_by_name(&self, name: &str) -> Option<&CoreInfo> {
+        self.cores.iter().find(|info| info.name == name)
+    }
+
+    pub fn find_by_extension(&self, extension: &str) -> Vec<&CoreInfo> {
+        self.cores
+            .iter()
+            .filter(|info| info.supports_extension(extension))
+            .collect()
+    }
+
+    #[doc = "Compara com systemname e systemid sem diferenciar maiúsculas."]
+    pub fn find_by_system(&self, system: &str) -> Vec<&CoreInfo> {
+        self.cores
+            .iter()
+            .filter(|info| {
+                info.system_name.eq_ignore_ascii_case(system)
+                    || info.system_id.eq_ignore_ascii_case(system)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test_core_info {
+    use crate::core_info::{CoreInfo, CoreInfoIndex};
+    use std::{fs, path::Path};
+
+    const PSX_INFO: &str = r#"# Software Information
+display_name = "Sony - PlayStation (Beetle PSX)"
+authors = "Mednafen Team|libretro"
+supported_extensions = "cue|toc|m3u|CHD|pbp"
+corename = "Beetle PSX"
+license = "GPLv2"
+
+# Hardware Information
+manufacturer = "Sony"
+systemname = "PlayStation"
+systemid = "playstation"
+
+database = "Sony - PlayStation"
+savestate = "true"
+
+firmware_count = 2
+firmware0_desc = "scph5500.bin (PS1 JP BIOS)"
+firmware0_path = "scph5500.bin"
+firmware0_opt = "false"
+firmware1_desc = "scph5501.bin (PS1 US BIOS)"
+firmware1_path = "scph5501.bin"
+firmware1_opt = "true"
+firmware1_md5 = "490F666E1AFB15B7362B406ED1CEA246"
+notes = "(!) scph5500.bin (md5): 8dd7d5296a650fac7319bce665a6a53c|(!) scph5501.bin (md5): 490f666e1afb15b7362b406ed1cea246"
+"#;
+
+    #[test]
+    fn parse_info() {
+        let info = CoreInfo::parse("mednafen_psx_libretro", PSX_INFO);
+
+        assert_eq!(info.display_name, "Sony - PlayStation (Beetle PSX)");
+        assert_eq!(info.system_name, "PlayStation");
+        assert_eq!(info.authors, vec!["Mednafen Team", "libretro"]);
+        assert_eq!(info.database, vec!["Sony - PlayStation"]);
+        assert!(info.savestate);
+        assert!(info.supports_extension(".chd"));
+        assert!(!info.supports_extension("iso"));
+        assert_eq!(info.notes.len(), 2);
+
+        assert_eq!(info.firmware.len(), 2);
+        assert!(!info.firmware[0].optional);
+        assert_eq!(
+            info.firmware[0].md5.as_deref(),
+            Some("8dd7d5296a650fac7319bce665a6a53c")
+        );
+        assert!(info.firmware[1].optional);
+        assert_eq!(
+            info.firmware[1].md5.as_deref(),
+            Some("490f666e1afb15b7362b406ed1cea246")
+        );
+
+        let missing = info.get_missing_firmware(Path::new("retro_out_test/core_info/system"));
+        assert_eq!(missing.len(), 1);
+        assert_eq!(missing[0].path, "scph5500.bin");
+    }
+
+    #[test]
+    fn index_from_dir() {
+        let dir = Path::new("retro_out_test/core_info/info");
+        fs::create_dir_all(dir).unwrap();
+
+        fs::write(dir.join("mednafen_psx_libretro.info"), PSX_INFO).unwrap();
+        fs::write(
+            dir.join("snes9x_libretro.info"),
+            "display_name = \"Nintendo - SNES / SFC (Snes9x - Current)\"\n\
+             supported_extensions = \"smc|sfc|swc|fig|bs|st\"\n\
+             systemname = \"Super Nintendo Entertainment System\"\n\
+             systemid = \"super_nes\"\n\
+             savestate = \"true\"\n",
+        )
+        .unwrap();
+        fs::write(dir.join("readme.txt"), "supported_extensions = \"sfc\"").unwrap();
+
+        let index = CoreInfoIndex::from_dir(dir).unwrap();
+        assert_eq!(index.cores.len(), 2);
+
+        let cores = index.find_by_extension("SFC");
+        assert_eq!(cores.len(), 1);
+        assert_eq!(cores[0].name, "snes9x_libretro");
+
+        assert_eq!(index.find_by_system("super_nes").len(), 1);
+        assert_eq!(index.find_by_system("playstation").len(), 1);
+        assert!(index.find_by_name("mednafen_psx_libretro").is_some());
+        assert!(index.find_by_extension("gba").is_empty());
+    }
+}
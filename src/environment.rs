@@ -1,41 +1,61 @@
 use crate::{
     binding::binding_log_interface,
     constants::{MAX_CORE_CONTROLLER_INFO_TYPES, MAX_CORE_SUBSYSTEM_INFO},
+    content::ContentInfoOverride,
     controller_info::ControllerInfo,
     core::CoreWrapper,
+    memory::MemoryRegion,
+    message::CoreMessage,
     retro_context::RetroContext,
     retro_perf::{
-        core_get_perf_counter, core_perf_log, core_perf_register, core_perf_start, core_perf_stop,
-        get_cpu_features, get_features_get_time_usec,
+        clear_perf_counters, core_get_perf_counter, core_perf_log, core_perf_register,
+        core_perf_start, core_perf_stop, get_cpu_features, get_features_get_time_usec,
     },
     retro_sys::{
-        retro_controller_info, retro_core_option_display, retro_core_options_v2_intl,
-        retro_game_geometry, retro_hw_context_type, retro_hw_render_callback, retro_language,
-        retro_log_level, retro_perf_callback, retro_pixel_format, retro_proc_address_t,
-        retro_rumble_effect, retro_rumble_interface, retro_subsystem_info, retro_variable,
-        RETRO_ENVIRONMENT_GET_AUDIO_VIDEO_ENABLE, RETRO_ENVIRONMENT_GET_CORE_ASSETS_DIRECTORY,
-        RETRO_ENVIRONMENT_GET_CORE_OPTIONS_VERSION,
-        RETRO_ENVIRONMENT_GET_DISK_CONTROL_INTERFACE_VERSION, RETRO_ENVIRONMENT_GET_INPUT_BITMASKS,
+        retro_audio_buffer_status_callback, retro_audio_callback, retro_controller_info,
+        retro_core_option_display, retro_core_options_v2_intl, retro_fastforwarding_override,
+        retro_frame_time_callback, retro_framebuffer, retro_game_geometry, retro_game_info_ext,
+        retro_hw_context_type, retro_hw_render_callback, retro_language, retro_led_interface,
+        retro_log_level, retro_memory_map, retro_message, retro_message_ext, retro_perf_callback,
+        retro_pixel_format, retro_proc_address_t, retro_rumble_effect, retro_rumble_interface,
+        retro_sensor_action, retro_sensor_interface, retro_subsystem_info,
+        retro_system_content_info_override, retro_throttle_state, retro_variable,
+        retro_vfs_interface, retro_vfs_interface_info, RETRO_ENVIRONMENT_GET_AUDIO_VIDEO_ENABLE,
+        RETRO_ENVIRONMENT_GET_CORE_ASSETS_DIRECTORY, RETRO_ENVIRONMENT_GET_CORE_OPTIONS_VERSION,
+        RETRO_ENVIRONMENT_GET_CURRENT_SOFTWARE_FRAMEBUFFER,
+        RETRO_ENVIRONMENT_GET_DISK_CONTROL_INTERFACE_VERSION, RETRO_ENVIRONMENT_GET_FASTFORWARDING,
+        RETRO_ENVIRONMENT_GET_GAME_INFO_EXT, RETRO_ENVIRONMENT_GET_INPUT_BITMASKS,
         RETRO_ENVIRONMENT_GET_LANGUAGE, RETRO_ENVIRONMENT_GET_LED_INTERFACE,
         RETRO_ENVIRONMENT_GET_LOG_INTERFACE, RETRO_ENVIRONMENT_GET_MESSAGE_INTERFACE_VERSION,
         RETRO_ENVIRONMENT_GET_PERF_INTERFACE, RETRO_ENVIRONMENT_GET_PREFERRED_HW_RENDER,
         RETRO_ENVIRONMENT_GET_RUMBLE_INTERFACE, RETRO_ENVIRONMENT_GET_SAVE_DIRECTORY,
-        RETRO_ENVIRONMENT_GET_SYSTEM_DIRECTORY, RETRO_ENVIRONMENT_GET_USERNAME,
+        RETRO_ENVIRONMENT_GET_SENSOR_INTERFACE, RETRO_ENVIRONMENT_GET_SYSTEM_DIRECTORY,
+        RETRO_ENVIRONMENT_GET_THROTTLE_STATE, RETRO_ENVIRONMENT_GET_USERNAME,
         RETRO_ENVIRONMENT_GET_VARIABLE, RETRO_ENVIRONMENT_GET_VARIABLE_UPDATE,
-        RETRO_ENVIRONMENT_GET_VFS_INTERFACE, RETRO_ENVIRONMENT_SET_CONTROLLER_INFO,
-        RETRO_ENVIRONMENT_SET_CORE_OPTIONS_DISPLAY,
+        RETRO_ENVIRONMENT_GET_VFS_INTERFACE, RETRO_ENVIRONMENT_SET_AUDIO_BUFFER_STATUS_CALLBACK,
+        RETRO_ENVIRONMENT_SET_AUDIO_CALLBACK, RETRO_ENVIRONMENT_SET_CONTENT_INFO_OVERRIDE,
+        RETRO_ENVIRONMENT_SET_CONTROLLER_INFO, RETRO_ENVIRONMENT_SET_CORE_OPTIONS_DISPLAY,
         RETRO_ENVIRONMENT_SET_CORE_OPTIONS_UPDATE_DISPLAY_CALLBACK,
         RETRO_ENVIRONMENT_SET_CORE_OPTIONS_V2_INTL, RETRO_ENVIRONMENT_SET_DISK_CONTROL_INTERFACE,
+        RETRO_ENVIRONMENT_SET_FASTFORWARDING_OVERRIDE, RETRO_ENVIRONMENT_SET_FRAME_TIME_CALLBACK,
         RETRO_ENVIRONMENT_SET_GEOMETRY, RETRO_ENVIRONMENT_SET_HW_RENDER,
-        RETRO_ENVIRONMENT_SET_INPUT_DESCRIPTORS, RETRO_ENVIRONMENT_SET_PERFORMANCE_LEVEL,
+        RETRO_ENVIRONMENT_SET_INPUT_DESCRIPTORS, RETRO_ENVIRONMENT_SET_MEMORY_MAPS,
+        RETRO_ENVIRONMENT_SET_MESSAGE, RETRO_ENVIRONMENT_SET_MESSAGE_EXT,
+        RETRO_ENVIRONMENT_SET_MINIMUM_AUDIO_LATENCY, RETRO_ENVIRONMENT_SET_PERFORMANCE_LEVEL,
         RETRO_ENVIRONMENT_SET_PIXEL_FORMAT, RETRO_ENVIRONMENT_SET_SERIALIZATION_QUIRKS,
         RETRO_ENVIRONMENT_SET_SUBSYSTEM_INFO, RETRO_ENVIRONMENT_SET_SUPPORT_ACHIEVEMENTS,
         RETRO_ENVIRONMENT_SET_SUPPORT_NO_GAME, RETRO_ENVIRONMENT_SET_VARIABLE,
         RETRO_ENVIRONMENT_SET_VARIABLES,
     },
+    speed::FastForwardOverride,
     tools::ffi_tools::{get_str_from_ptr, make_c_string},
+    vfs::{
+        ffi::{VFS_INTERFACE, VFS_INTERFACE_VERSION},
+        VfsBackend,
+    },
 };
 use ::std::os::raw;
+use log::{debug, log, trace, Level};
 use std::mem;
 use std::{os::raw::c_void, ptr::addr_of, sync::Arc};
 
@@ -55,6 +75,24 @@ pub struct RetroEnvCallbacks {
     pub context_destroy: fn(),
 }
 
+#[doc = "
+    Callbacks que o frontend pode ignorar, enviadas com [CoreWrapper::set_optional_callbacks].
+    Ficam fora de [RetroEnvCallbacks] para que as novas callbacks nao quebrem os frontends existentes.
+"]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RetroOptionalCallbacks {
+    #[doc = "Chamada apenas quando o núcleo muda o estado de um LED."]
+    pub led_callback: Option<fn(led: i32, state: i32)>,
+    #[doc = "Chamada a cada mensagem enviada pelo núcleo, a mensagem tambem fica em [CoreWrapper::messages]."]
+    pub message_callback: Option<fn(message: &CoreMessage)>,
+    #[doc = "Recebe cada linha enviada pela log interface do núcleo, a mesma linha tambem e enviada para o [log]."]
+    pub log_callback: Option<fn(level: retro_log_level, message: &str)>,
+    #[doc = "Chamada quando o núcleo pede para forçar ou liberar o fast-forward."]
+    pub fastforwarding_override_callback: Option<fn(ff_override: &FastForwardOverride)>,
+    #[doc = "Chamada depois de cada retro_run com as memorias do núcleo, usada para avaliar conquistas."]
+    pub achievements_callback: Option<fn(regions: &[MemoryRegion])>,
+}
+
 static mut CORE_CONTEXT: Option<Arc<CoreWrapper>> = None;
 
 pub fn configure(core_ctx: Arc<CoreWrapper>) {
@@ -67,6 +105,30 @@ pub fn delete_local_core_ctx() {
     unsafe {
         CORE_CONTEXT = None;
     }
+
+    clear_perf_counters();
+}
+
+pub(crate) fn get_vfs_backend() -> Option<Arc<dyn VfsBackend>> {
+    unsafe {
+        (*addr_of!(CORE_CONTEXT))
+            .as_ref()
+            .map(|core_ctx| core_ctx.vfs.read().unwrap().clone())
+    }
+}
+
+#[doc = "Id do [RetroContext] associado ao núcleo atual, usado para identificar os logs."]
+pub(crate) fn get_ctx_label() -> String {
+    unsafe {
+        match &*addr_of!(CORE_CONTEXT) {
+            Some(core_ctx) => core_ctx.retro_ctx_associated.to_string(),
+            None => "-".to_string(),
+        }
+    }
+}
+
+fn trace_env_call(cmd: &str) {
+    trace!("[{}] {}", get_ctx_label(), cmd);
 }
 
 fn _force_stop() {
@@ -96,6 +158,8 @@ pub unsafe extern "C" fn audio_sample_batch_callback(data: *const i16, frames: u
 
 pub unsafe extern "C" fn input_poll_callback() {
     if let Some(core_ctx) = &*addr_of!(CORE_CONTEXT) {
+        core_ctx.movie.on_input_poll();
+        core_ctx.remaps.on_input_poll();
         (core_ctx.callbacks.input_poll_callback)()
     }
 }
@@ -107,12 +171,15 @@ pub unsafe extern "C" fn input_state_callback(
     id: raw::c_uint,
 ) -> i16 {
     match &*addr_of!(CORE_CONTEXT) {
-        Some(core_ctx) => (core_ctx.callbacks.input_state_callback)(
-            port as i16,
-            device as i16,
-            index as i16,
-            id as i16,
-        ),
+        Some(core_ctx) => core_ctx.movie.input_state(port, device, index, id, || {
+            core_ctx.remaps.input_state(
+                port,
+                device,
+                index,
+                id,
+                core_ctx.callbacks.input_state_callback,
+            )
+        }),
         None => 0,
     }
 }
@@ -137,14 +204,73 @@ unsafe extern "C" fn rumble_callback(
     strength: u16,
 ) -> bool {
     match &*addr_of!(CORE_CONTEXT) {
-        Some(core_ctx) => (core_ctx.callbacks.rumble_callback)(port, effect, strength),
+        Some(core_ctx) => {
+            if !*core_ctx.rumble.enabled.read().unwrap() {
+                core_ctx.rumble.set_state(port, effect, strength);
+                return false;
+            }
+
+            if core_ctx.rumble.set_state(port, effect, strength) {
+                let strength = core_ctx.rumble.scale(strength);
+                (core_ctx.callbacks.rumble_callback)(port, effect, strength)
+            } else {
+                true
+            }
+        }
+        None => false,
+    }
+}
+
+unsafe extern "C" fn led_callback(led: raw::c_int, state: raw::c_int) {
+    if led < 0 {
+        return;
+    }
+
+    if let Some(core_ctx) = &*addr_of!(CORE_CONTEXT) {
+        if core_ctx.set_led_state(led, state) {
+            if let Some(led_callback) = core_ctx.get_optional_callbacks().led_callback {
+                led_callback(led, state);
+            }
+        }
+    }
+}
+
+unsafe extern "C" fn set_sensor_state(
+    port: raw::c_uint,
+    action: retro_sensor_action,
+    rate: raw::c_uint,
+) -> bool {
+    match &*addr_of!(CORE_CONTEXT) {
+        Some(core_ctx) => core_ctx.sensors.set_state(port, action, rate),
         None => false,
     }
 }
 
-unsafe extern "C" fn core_log(_level: retro_log_level, _log: *const raw::c_char) {
-    #[cfg(feature = "core_logs")]
-    println!("[{:?}]: {:?}", _level, get_str_from_ptr(_log));
+unsafe extern "C" fn get_sensor_input(port: raw::c_uint, id: raw::c_uint) -> f32 {
+    match &*addr_of!(CORE_CONTEXT) {
+        Some(core_ctx) => core_ctx.sensors.get_input(port, id),
+        None => 0.0,
+    }
+}
+
+unsafe extern "C" fn core_log(level: retro_log_level, log: *const raw::c_char) {
+    let message = get_str_from_ptr(log);
+    let message = message.trim_end();
+
+    let log_level = match level {
+        retro_log_level::RETRO_LOG_DEBUG => Level::Debug,
+        retro_log_level::RETRO_LOG_WARN => Level::Warn,
+        retro_log_level::RETRO_LOG_ERROR => Level::Error,
+        _ => Level::Info,
+    };
+
+    log!(target: "retro_ab::core", log_level, "[{}] {}", get_ctx_label(), message);
+
+    if let Some(core_ctx) = &*addr_of!(CORE_CONTEXT) {
+        if let Some(log_callback) = core_ctx.get_optional_callbacks().log_callback {
+            log_callback(level, message);
+        }
+    }
 }
 
 unsafe extern "C" fn get_current_frame_buffer() -> usize {
@@ -196,8 +322,7 @@ unsafe extern "C" fn context_destroy() {
 pub unsafe extern "C" fn core_environment(cmd: raw::c_uint, data: *mut c_void) -> bool {
     match cmd {
         RETRO_ENVIRONMENT_SET_SUPPORT_NO_GAME => {
-            #[cfg(feature = "core_logs")]
-            println!("RETRO_ENVIRONMENT_SET_SUPPORT_NO_GAME -> ok");
+            trace_env_call("RETRO_ENVIRONMENT_SET_SUPPORT_NO_GAME -> ok");
 
             match &*addr_of!(CORE_CONTEXT) {
                 Some(core_ctx) => {
@@ -209,8 +334,7 @@ pub unsafe extern "C" fn core_environment(cmd: raw::c_uint, data: *mut c_void) -
             return true;
         }
         RETRO_ENVIRONMENT_GET_SYSTEM_DIRECTORY => {
-            #[cfg(feature = "core_logs")]
-            println!("RETRO_ENVIRONMENT_GET_SYSTEM_DIRECTORY -> ok");
+            trace_env_call("RETRO_ENVIRONMENT_GET_SYSTEM_DIRECTORY -> ok");
 
             match &*addr_of!(CORE_CONTEXT) {
                 Some(core_ctx) => {
@@ -224,8 +348,7 @@ pub unsafe extern "C" fn core_environment(cmd: raw::c_uint, data: *mut c_void) -
             return true;
         }
         RETRO_ENVIRONMENT_GET_SAVE_DIRECTORY => {
-            #[cfg(feature = "core_logs")]
-            println!("RETRO_ENVIRONMENT_GET_SAVE_DIRECTORY -> ok");
+            trace_env_call("RETRO_ENVIRONMENT_GET_SAVE_DIRECTORY -> ok");
 
             match &*addr_of!(CORE_CONTEXT) {
                 Some(core_ctx) => {
@@ -239,8 +362,7 @@ pub unsafe extern "C" fn core_environment(cmd: raw::c_uint, data: *mut c_void) -
             return true;
         }
         RETRO_ENVIRONMENT_GET_CORE_ASSETS_DIRECTORY => {
-            #[cfg(feature = "core_logs")]
-            println!("RETRO_ENVIRONMENT_GET_CORE_ASSETS_DIRECTORY -> ok");
+            trace_env_call("RETRO_ENVIRONMENT_GET_CORE_ASSETS_DIRECTORY -> ok");
 
             match &*addr_of!(CORE_CONTEXT) {
                 Some(core_ctx) => {
@@ -254,22 +376,27 @@ pub unsafe extern "C" fn core_environment(cmd: raw::c_uint, data: *mut c_void) -
             return true;
         }
         RETRO_ENVIRONMENT_SET_SUPPORT_ACHIEVEMENTS => {
-            #[cfg(feature = "core_logs")]
-            println!("RETRO_ENVIRONMENT_SET_SUPPORT_ACHIEVEMENTS");
+            trace_env_call("RETRO_ENVIRONMENT_SET_SUPPORT_ACHIEVEMENTS -> ok");
+
+            match &*addr_of!(CORE_CONTEXT) {
+                Some(core_ctx) => {
+                    *core_ctx.system.support_achievements.write().unwrap() = *(data as *const bool);
+                }
+                _ => return false,
+            }
+
+            return true;
         }
         RETRO_ENVIRONMENT_SET_PERFORMANCE_LEVEL => {
-            #[cfg(feature = "core_logs")]
-            println!("RETRO_ENVIRONMENT_SET_PERFORMANCE_LEVEL");
+            trace_env_call("RETRO_ENVIRONMENT_SET_PERFORMANCE_LEVEL");
         }
         RETRO_ENVIRONMENT_GET_CORE_OPTIONS_VERSION => {
-            #[cfg(feature = "core_logs")]
-            println!("RETRO_ENVIRONMENT_GET_CORE_OPTIONS_VERSION -> ok");
+            trace_env_call("RETRO_ENVIRONMENT_GET_CORE_OPTIONS_VERSION -> ok");
             *(data as *mut u32) = 2;
             return true;
         }
         RETRO_ENVIRONMENT_SET_CORE_OPTIONS_V2_INTL => {
-            #[cfg(feature = "core_logs")]
-            println!("RETRO_ENVIRONMENT_SET_CORE_OPTIONS_V2_INTL -> ok");
+            trace_env_call("RETRO_ENVIRONMENT_SET_CORE_OPTIONS_V2_INTL -> ok");
 
             match &*addr_of!(CORE_CONTEXT) {
                 Some(core_ctx) => {
@@ -284,8 +411,7 @@ pub unsafe extern "C" fn core_environment(cmd: raw::c_uint, data: *mut c_void) -
             return true;
         }
         RETRO_ENVIRONMENT_SET_CORE_OPTIONS_DISPLAY => {
-            #[cfg(feature = "core_logs")]
-            println!("RETRO_ENVIRONMENT_SET_CORE_OPTIONS_DISPLAY -> ok");
+            trace_env_call("RETRO_ENVIRONMENT_SET_CORE_OPTIONS_DISPLAY -> ok");
 
             match &*addr_of!(CORE_CONTEXT) {
                 Some(core_ctx) => {
@@ -301,12 +427,10 @@ pub unsafe extern "C" fn core_environment(cmd: raw::c_uint, data: *mut c_void) -
             return true;
         }
         RETRO_ENVIRONMENT_SET_CORE_OPTIONS_UPDATE_DISPLAY_CALLBACK => {
-            #[cfg(feature = "core_logs")]
-            println!("RETRO_ENVIRONMENT_SET_CORE_OPTIONS_UPDATE_DISPLAY_CALLBACK");
+            trace_env_call("RETRO_ENVIRONMENT_SET_CORE_OPTIONS_UPDATE_DISPLAY_CALLBACK");
         }
         RETRO_ENVIRONMENT_GET_LANGUAGE => {
-            #[cfg(feature = "core_logs")]
-            println!("RETRO_ENVIRONMENT_GET_LANGUAGE -> ok");
+            trace_env_call("RETRO_ENVIRONMENT_GET_LANGUAGE -> ok");
             *(data as *mut retro_language) = retro_language::RETRO_LANGUAGE_ENGLISH;
             match &*addr_of!(CORE_CONTEXT) {
                 Some(core_ctx) => {
@@ -316,9 +440,207 @@ pub unsafe extern "C" fn core_environment(cmd: raw::c_uint, data: *mut c_void) -
             }
             return true;
         }
+        RETRO_ENVIRONMENT_SET_FRAME_TIME_CALLBACK => {
+            trace_env_call("RETRO_ENVIRONMENT_SET_FRAME_TIME_CALLBACK -> ok");
+
+            match &*addr_of!(CORE_CONTEXT) {
+                Some(core_ctx) => {
+                    let frame_time = *(data as *mut retro_frame_time_callback);
+
+                    *core_ctx.frame_time_callback.lock().unwrap() =
+                        frame_time.callback.map(|_| frame_time);
+                    *core_ctx.last_frame_time.lock().unwrap() = None;
+                }
+                _ => return false,
+            }
+
+            return true;
+        }
+        RETRO_ENVIRONMENT_SET_AUDIO_CALLBACK => {
+            trace_env_call("RETRO_ENVIRONMENT_SET_AUDIO_CALLBACK -> ok");
+
+            match &*addr_of!(CORE_CONTEXT) {
+                Some(core_ctx) => {
+                    let audio_callback = *(data as *mut retro_audio_callback);
+
+                    *core_ctx.audio_callback.lock().unwrap() =
+                        audio_callback.callback.map(|_| audio_callback);
+                }
+                _ => return false,
+            }
+
+            return true;
+        }
+        RETRO_ENVIRONMENT_SET_AUDIO_BUFFER_STATUS_CALLBACK => {
+            trace_env_call("RETRO_ENVIRONMENT_SET_AUDIO_BUFFER_STATUS_CALLBACK -> ok");
+
+            match &*addr_of!(CORE_CONTEXT) {
+                Some(core_ctx) => {
+                    //com data nulo o núcleo quer remover a callback
+                    let callback = (data as *mut retro_audio_buffer_status_callback)
+                        .as_ref()
+                        .and_then(|raw| raw.callback);
+
+                    *core_ctx
+                        .av_info
+                        .audio
+                        .buffer_status_callback
+                        .lock()
+                        .unwrap() = callback;
+                }
+                _ => return false,
+            }
+
+            return true;
+        }
+        RETRO_ENVIRONMENT_SET_MINIMUM_AUDIO_LATENCY => {
+            trace_env_call("RETRO_ENVIRONMENT_SET_MINIMUM_AUDIO_LATENCY -> ok");
+
+            match &*addr_of!(CORE_CONTEXT) {
+                Some(core_ctx) => {
+                    let latency = (data as *mut u32).as_ref().copied().unwrap_or(0);
+
+                    *core_ctx.av_info.audio.minimum_latency.lock().unwrap() = latency;
+                }
+                _ => return false,
+            }
+
+            return true;
+        }
+        RETRO_ENVIRONMENT_GET_FASTFORWARDING => {
+            trace_env_call("RETRO_ENVIRONMENT_GET_FASTFORWARDING -> ok");
+
+            if data.is_null() {
+                return false;
+            }
+
+            match &*addr_of!(CORE_CONTEXT) {
+                Some(core_ctx) => {
+                    *(data as *mut bool) = core_ctx.get_speed().is_fast_forward();
+                }
+                _ => return false,
+            }
+
+            return true;
+        }
+        RETRO_ENVIRONMENT_GET_THROTTLE_STATE => {
+            trace_env_call("RETRO_ENVIRONMENT_GET_THROTTLE_STATE -> ok");
+
+            if data.is_null() {
+                return false;
+            }
+
+            match &*addr_of!(CORE_CONTEXT) {
+                Some(core_ctx) => {
+                    let fps = *core_ctx.av_info.timing.fps.read().unwrap();
+
+                    *(data as *mut retro_throttle_state) =
+                        core_ctx.get_speed().to_throttle_state(fps);
+                }
+                _ => return false,
+            }
+
+            return true;
+        }
+        RETRO_ENVIRONMENT_SET_FASTFORWARDING_OVERRIDE => {
+            trace_env_call("RETRO_ENVIRONMENT_SET_FASTFORWARDING_OVERRIDE -> ok");
+
+            //com data nulo o núcleo so quer saber se o frontend suporta o override
+            if data.is_null() {
+                return true;
+            }
+
+            match &*addr_of!(CORE_CONTEXT) {
+                Some(core_ctx) => {
+                    let ff_override = FastForwardOverride::from_raw(
+                        &*(data as *mut retro_fastforwarding_override),
+                    );
+
+                    *core_ctx.fastforward_override.lock().unwrap() = Some(ff_override);
+
+                    if let Some(ff_callback) = core_ctx
+                        .get_optional_callbacks()
+                        .fastforwarding_override_callback
+                    {
+                        ff_callback(&ff_override);
+                    }
+                }
+                _ => return false,
+            }
+
+            return true;
+        }
+        RETRO_ENVIRONMENT_GET_CURRENT_SOFTWARE_FRAMEBUFFER => {
+            trace_env_call("RETRO_ENVIRONMENT_GET_CURRENT_SOFTWARE_FRAMEBUFFER -> ok");
+
+            match &*addr_of!(CORE_CONTEXT) {
+                Some(core_ctx) => {
+                    if data.is_null() {
+                        return false;
+                    }
+
+                    let framebuffer = &mut *(data as *mut retro_framebuffer);
+
+                    return core_ctx.av_info.video.get_software_framebuffer(framebuffer);
+                }
+                _ => return false,
+            }
+        }
+        RETRO_ENVIRONMENT_SET_MEMORY_MAPS => {
+            trace_env_call("RETRO_ENVIRONMENT_SET_MEMORY_MAPS -> ok");
+
+            match &*addr_of!(CORE_CONTEXT) {
+                Some(core_ctx) => {
+                    core_ctx
+                        .memory
+                        .set_memory_maps(data as *const retro_memory_map);
+                }
+                _ => return false,
+            }
+
+            return true;
+        }
+        RETRO_ENVIRONMENT_SET_CONTENT_INFO_OVERRIDE => {
+            trace_env_call("RETRO_ENVIRONMENT_SET_CONTENT_INFO_OVERRIDE -> ok");
+
+            //com data nulo o núcleo so quer saber se o frontend suporta o override
+            if data.is_null() {
+                return true;
+            }
+
+            match &*addr_of!(CORE_CONTEXT) {
+                Some(core_ctx) => {
+                    *core_ctx.content_overrides.write().unwrap() =
+                        ContentInfoOverride::from_raw_list(
+                            data as *const retro_system_content_info_override,
+                        );
+                }
+                _ => return false,
+            }
+
+            return true;
+        }
+        RETRO_ENVIRONMENT_GET_GAME_INFO_EXT => {
+            trace_env_call("RETRO_ENVIRONMENT_GET_GAME_INFO_EXT -> ok");
+
+            match &*addr_of!(CORE_CONTEXT) {
+                Some(core_ctx) => {
+                    let game_info_ext = core_ctx.game_info_ext.lock().unwrap();
+
+                    if game_info_ext.is_empty() {
+                        return false;
+                    }
+
+                    *(data as *mut *const retro_game_info_ext) =
+                        game_info_ext.as_ptr() as *const retro_game_info_ext;
+                }
+                _ => return false,
+            }
+
+            return true;
+        }
         RETRO_ENVIRONMENT_SET_GEOMETRY => {
-            #[cfg(feature = "core_logs")]
-            println!("RETRO_ENVIRONMENT_SET_GEOMETRY -> ok");
+            trace_env_call("RETRO_ENVIRONMENT_SET_GEOMETRY -> ok");
             let raw_geometry_ptr = data as *mut retro_game_geometry;
 
             if raw_geometry_ptr.is_null() {
@@ -335,8 +657,7 @@ pub unsafe extern "C" fn core_environment(cmd: raw::c_uint, data: *mut c_void) -
             return true;
         }
         RETRO_ENVIRONMENT_SET_PIXEL_FORMAT => {
-            #[cfg(feature = "core_logs")]
-            println!("RETRO_ENVIRONMENT_SET_PIXEL_FORMAT -> ok");
+            trace_env_call("RETRO_ENVIRONMENT_SET_PIXEL_FORMAT -> ok");
 
             match &*addr_of!(CORE_CONTEXT) {
                 Some(core_ctx) => {
@@ -348,8 +669,7 @@ pub unsafe extern "C" fn core_environment(cmd: raw::c_uint, data: *mut c_void) -
             return true;
         }
         RETRO_ENVIRONMENT_GET_VARIABLE_UPDATE => {
-            #[cfg(feature = "core_logs")]
-            println!("RETRO_ENVIRONMENT_GET_VARIABLE_UPDATE -> ok");
+            trace_env_call("RETRO_ENVIRONMENT_GET_VARIABLE_UPDATE -> ok");
 
             match &*addr_of!(CORE_CONTEXT) {
                 Some(core_ctx) => {
@@ -365,12 +685,10 @@ pub unsafe extern "C" fn core_environment(cmd: raw::c_uint, data: *mut c_void) -
             return true;
         }
         RETRO_ENVIRONMENT_SET_VARIABLES => {
-            #[cfg(feature = "core_logs")]
-            println!("RETRO_ENVIRONMENT_SET_VARIABLES");
+            trace_env_call("RETRO_ENVIRONMENT_SET_VARIABLES");
         }
         RETRO_ENVIRONMENT_GET_VARIABLE => {
-            #[cfg(feature = "core_logs")]
-            println!("RETRO_ENVIRONMENT_GET_VARIABLE -> ok");
+            trace_env_call("RETRO_ENVIRONMENT_GET_VARIABLE -> ok");
 
             let raw_variable = data as *const retro_variable;
 
@@ -408,20 +726,17 @@ pub unsafe extern "C" fn core_environment(cmd: raw::c_uint, data: *mut c_void) -
             }
         }
         RETRO_ENVIRONMENT_SET_INPUT_DESCRIPTORS => {
-            #[cfg(feature = "core_logs")]
-            println!("RETRO_ENVIRONMENT_SET_INPUT_DESCRIPTORS");
+            trace_env_call("RETRO_ENVIRONMENT_SET_INPUT_DESCRIPTORS");
         }
         RETRO_ENVIRONMENT_GET_LOG_INTERFACE => {
-            #[cfg(feature = "core_logs")]
-            println!("RETRO_ENVIRONMENT_GET_LOG_INTERFACE -> ok");
+            trace_env_call("RETRO_ENVIRONMENT_GET_LOG_INTERFACE -> ok");
 
             binding_log_interface::configure_log_interface(Some(core_log), data);
 
             return true;
         }
         RETRO_ENVIRONMENT_SET_SUBSYSTEM_INFO => {
-            #[cfg(feature = "core_logs")]
-            println!("RETRO_ENVIRONMENT_SET_SUBSYSTEM_INFO -> OK");
+            trace_env_call("RETRO_ENVIRONMENT_SET_SUBSYSTEM_INFO -> OK");
 
             match &*addr_of!(CORE_CONTEXT) {
                 Some(core_ctx) => {
@@ -436,13 +751,11 @@ pub unsafe extern "C" fn core_environment(cmd: raw::c_uint, data: *mut c_void) -
             return true;
         }
         RETRO_ENVIRONMENT_GET_INPUT_BITMASKS => {
-            #[cfg(feature = "core_logs")]
-            println!("RETRO_ENVIRONMENT_GET_INPUT_BITMASKS -> ok");
+            trace_env_call("RETRO_ENVIRONMENT_GET_INPUT_BITMASKS -> ok");
             return true;
         }
         RETRO_ENVIRONMENT_SET_CONTROLLER_INFO => {
-            #[cfg(feature = "core_logs")]
-            println!("RETRO_ENVIRONMENT_SET_CONTROLLER_INFO -> ok");
+            trace_env_call("RETRO_ENVIRONMENT_SET_CONTROLLER_INFO -> ok");
 
             match &*addr_of!(CORE_CONTEXT) {
                 Some(core_ctx) => {
@@ -467,38 +780,99 @@ pub unsafe extern "C" fn core_environment(cmd: raw::c_uint, data: *mut c_void) -
             return true;
         }
         RETRO_ENVIRONMENT_GET_AUDIO_VIDEO_ENABLE => {
-            #[cfg(feature = "core_logs")]
-            println!("RETRO_ENVIRONMENT_GET_AUDIO_VIDEO_ENABLE -> ok");
+            trace_env_call("RETRO_ENVIRONMENT_GET_AUDIO_VIDEO_ENABLE -> ok");
 
             *(data as *mut u32) = 1 << 0 | 1 << 1;
 
             return true;
         }
         RETRO_ENVIRONMENT_GET_VFS_INTERFACE => {
-            #[cfg(feature = "core_logs")]
-            println!("RETRO_ENVIRONMENT_GET_VFS_INTERFACE");
+            trace_env_call("RETRO_ENVIRONMENT_GET_VFS_INTERFACE -> ok");
+
+            let vfs_info = &mut *(data as *mut retro_vfs_interface_info);
+
+            if vfs_info.required_interface_version > VFS_INTERFACE_VERSION {
+                return false;
+            }
+
+            vfs_info.required_interface_version = VFS_INTERFACE_VERSION;
+            vfs_info.iface = &VFS_INTERFACE as *const retro_vfs_interface as *mut _;
+
+            return true;
         }
         RETRO_ENVIRONMENT_GET_LED_INTERFACE => {
-            #[cfg(feature = "core_logs")]
-            println!("RETRO_ENVIRONMENT_GET_LED_INTERFACE");
+            trace_env_call("RETRO_ENVIRONMENT_GET_LED_INTERFACE -> ok");
+
+            let led_raw = &mut *(data as *mut retro_led_interface);
+            led_raw.set_led_state = Some(led_callback);
+
+            return true;
+        }
+        RETRO_ENVIRONMENT_GET_SENSOR_INTERFACE => {
+            trace_env_call("RETRO_ENVIRONMENT_GET_SENSOR_INTERFACE -> ok");
+
+            let sensor_raw = &mut *(data as *mut retro_sensor_interface);
+            sensor_raw.set_sensor_state = Some(set_sensor_state);
+            sensor_raw.get_sensor_input = Some(get_sensor_input);
+
+            return true;
         }
         RETRO_ENVIRONMENT_GET_MESSAGE_INTERFACE_VERSION => {
-            #[cfg(feature = "core_logs")]
-            println!("RETRO_ENVIRONMENT_GET_MESSAGE_INTERFACE_VERSION");
+            trace_env_call("RETRO_ENVIRONMENT_GET_MESSAGE_INTERFACE_VERSION -> ok");
+
+            *(data as *mut u32) = 1;
+
+            return true;
+        }
+        RETRO_ENVIRONMENT_SET_MESSAGE => {
+            trace_env_call("RETRO_ENVIRONMENT_SET_MESSAGE -> ok");
+
+            if data.is_null() {
+                return false;
+            }
+
+            match &*addr_of!(CORE_CONTEXT) {
+                Some(core_ctx) => {
+                    let message = CoreMessage::from_raw(&*(data as *mut retro_message));
+                    core_ctx.push_message(message);
+
+                    return true;
+                }
+                _ => return false,
+            }
+        }
+        RETRO_ENVIRONMENT_SET_MESSAGE_EXT => {
+            trace_env_call("RETRO_ENVIRONMENT_SET_MESSAGE_EXT -> ok");
+
+            if data.is_null() {
+                return false;
+            }
+
+            match &*addr_of!(CORE_CONTEXT) {
+                Some(core_ctx) => {
+                    let message = CoreMessage::from_raw_ext(&*(data as *mut retro_message_ext));
+                    core_ctx.push_message(message);
+
+                    return true;
+                }
+                _ => return false,
+            }
         }
         RETRO_ENVIRONMENT_GET_DISK_CONTROL_INTERFACE_VERSION => {
-            #[cfg(feature = "core_logs")]
-            println!("RETRO_ENVIRONMENT_GET_DISK_CONTROL_INTERFACE_VERSION");
+            trace_env_call("RETRO_ENVIRONMENT_GET_DISK_CONTROL_INTERFACE_VERSION");
         }
         RETRO_ENVIRONMENT_SET_DISK_CONTROL_INTERFACE => {
-            #[cfg(feature = "core_logs")]
-            println!("RETRO_ENVIRONMENT_SET_DISK_CONTROL_INTERFACE");
+            trace_env_call("RETRO_ENVIRONMENT_SET_DISK_CONTROL_INTERFACE");
         }
         RETRO_ENVIRONMENT_GET_PERF_INTERFACE => {
-            #[cfg(feature = "core_logs")]
-            println!("RETRO_ENVIRONMENT_GET_PERF_INTERFACE -> ok");
+            trace_env_call("RETRO_ENVIRONMENT_GET_PERF_INTERFACE -> ok");
+
+            if data.is_null() {
+                return false;
+            }
 
-            let mut perf = *(data as *mut retro_perf_callback);
+            //o núcleo lê as funções da struct que ele mesmo enviou
+            let perf = &mut *(data as *mut retro_perf_callback);
 
             perf.get_time_usec = Some(get_features_get_time_usec);
             perf.get_cpu_features = Some(get_cpu_features);
@@ -511,12 +885,10 @@ pub unsafe extern "C" fn core_environment(cmd: raw::c_uint, data: *mut c_void) -
             return true;
         }
         RETRO_ENVIRONMENT_SET_SERIALIZATION_QUIRKS => {
-            #[cfg(feature = "core_logs")]
-            println!("RETRO_ENVIRONMENT_SET_SERIALIZATION_QUIRKS");
+            trace_env_call("RETRO_ENVIRONMENT_SET_SERIALIZATION_QUIRKS");
         }
         RETRO_ENVIRONMENT_GET_PREFERRED_HW_RENDER => {
-            #[cfg(feature = "core_logs")]
-            println!("RETRO_ENVIRONMENT_GET_PREFERRED_HW_RENDER");
+            trace_env_call("RETRO_ENVIRONMENT_GET_PREFERRED_HW_RENDER");
 
             match &*addr_of!(CORE_CONTEXT) {
                 Some(core_ctx) => {
@@ -529,8 +901,7 @@ pub unsafe extern "C" fn core_environment(cmd: raw::c_uint, data: *mut c_void) -
             return false;
         }
         RETRO_ENVIRONMENT_SET_HW_RENDER => {
-            #[cfg(feature = "core_logs")]
-            println!("RETRO_ENVIRONMENT_SET_HW_RENDER");
+            trace_env_call("RETRO_ENVIRONMENT_SET_HW_RENDER");
 
             let mut data = *(data as *mut retro_hw_render_callback);
 
@@ -574,24 +945,21 @@ pub unsafe extern "C" fn core_environment(cmd: raw::c_uint, data: *mut c_void) -
             return false;
         }
         RETRO_ENVIRONMENT_SET_VARIABLE => {
-            #[cfg(feature = "core_logs")]
-            println!("RETRO_ENVIRONMENT_SET_VARIABLE");
+            trace_env_call("RETRO_ENVIRONMENT_SET_VARIABLE");
         }
         RETRO_ENVIRONMENT_GET_USERNAME => {
-            #[cfg(feature = "core_logs")]
-            println!("RETRO_ENVIRONMENT_GET_USERNAME");
+            trace_env_call("RETRO_ENVIRONMENT_GET_USERNAME");
         }
         RETRO_ENVIRONMENT_GET_RUMBLE_INTERFACE => {
-            #[cfg(feature = "core_logs")]
-            println!("RETRO_ENVIRONMENT_GET_RUMBLE_INTERFACE -> ok");
+            trace_env_call("RETRO_ENVIRONMENT_GET_RUMBLE_INTERFACE -> ok");
 
-            let mut rumble_raw = *(data as *mut retro_rumble_interface);
+            let rumble_raw = &mut *(data as *mut retro_rumble_interface);
             rumble_raw.set_rumble_state = Some(rumble_callback);
 
             return true;
         }
         _ => {
-            println!("{:?}", cmd);
+            debug!("[{}] comando nao suportado: {}", get_ctx_label(), cmd);
             return false;
         }
     }
@@ -605,7 +973,11 @@ mod test_environment {
 
     use crate::{
         binding::binding_libretro::{
-            retro_pixel_format, RETRO_ENVIRONMENT_GET_INPUT_BITMASKS,
+            retro_led_interface, retro_pixel_format, retro_sensor_interface,
+            retro_vfs_interface_info, RETRO_ENVIRONMENT_GET_INPUT_BITMASKS,
+            RETRO_ENVIRONMENT_GET_LED_INTERFACE, RETRO_ENVIRONMENT_GET_MESSAGE_INTERFACE_VERSION,
+            RETRO_ENVIRONMENT_GET_SENSOR_INTERFACE, RETRO_ENVIRONMENT_GET_VFS_INTERFACE,
+            RETRO_ENVIRONMENT_SET_MESSAGE, RETRO_ENVIRONMENT_SET_MESSAGE_EXT,
             RETRO_ENVIRONMENT_SET_PIXEL_FORMAT,
         },
         environment::{configure, CORE_CONTEXT},
@@ -631,6 +1003,76 @@ mod test_environment {
         assert_eq!(my_bool, true);
     }
 
+    #[test]
+    fn led_interface() {
+        let mut led = retro_led_interface {
+            set_led_state: None,
+        };
+        let data = &mut led as *mut retro_led_interface as *mut c_void;
+
+        let result = unsafe { core_environment(RETRO_ENVIRONMENT_GET_LED_INTERFACE, data) };
+
+        assert_eq!(result, true);
+        assert!(led.set_led_state.is_some());
+    }
+
+    #[test]
+    fn sensor_interface() {
+        let mut sensor = retro_sensor_interface {
+            set_sensor_state: None,
+            get_sensor_input: None,
+        };
+        let data = &mut sensor as *mut retro_sensor_interface as *mut c_void;
+
+        let result = unsafe { core_environment(RETRO_ENVIRONMENT_GET_SENSOR_INTERFACE, data) };
+
+        assert_eq!(result, true);
+        assert!(sensor.set_sensor_state.is_some());
+        assert!(sensor.get_sensor_input.is_some());
+    }
+
+    #[test]
+    fn message_interface_version() {
+        let mut version = 0u32;
+        let data = &mut version as *mut u32 as *mut c_void;
+
+        let result =
+            unsafe { core_environment(RETRO_ENVIRONMENT_GET_MESSAGE_INTERFACE_VERSION, data) };
+
+        assert_eq!(result, true);
+        assert_eq!(version, 1);
+    }
+
+    #[test]
+    fn null_message() {
+        let data = std::ptr::null_mut();
+
+        assert!(!unsafe { core_environment(RETRO_ENVIRONMENT_SET_MESSAGE, data) });
+        assert!(!unsafe { core_environment(RETRO_ENVIRONMENT_SET_MESSAGE_EXT, data) });
+    }
+
+    #[test]
+    fn vfs_interface() {
+        let mut vfs_info = retro_vfs_interface_info {
+            required_interface_version: 4,
+            iface: std::ptr::null_mut(),
+        };
+        let data = &mut vfs_info as *mut retro_vfs_interface_info as *mut c_void;
+
+        let result = unsafe { core_environment(RETRO_ENVIRONMENT_GET_VFS_INTERFACE, data) };
+
+        assert_eq!(result, false);
+        assert!(vfs_info.iface.is_null());
+
+        vfs_info.required_interface_version = 1;
+
+        let result = unsafe { core_environment(RETRO_ENVIRONMENT_GET_VFS_INTERFACE, data) };
+
+        assert_eq!(result, true);
+        assert_eq!(vfs_info.required_interface_version, 3);
+        assert!(!vfs_info.iface.is_null());
+    }
+
     #[test]
     fn pixel_format() {
         cfg_test();
@@ -1,27 +1,42 @@
 extern crate libloading;
 extern crate uuid;
 
+mod archive;
 mod av_info;
 mod binding;
 mod constants;
+mod content;
+mod content_identity;
 mod controller_info;
 mod environment;
 mod managers;
+mod memory;
+mod message;
+mod patch;
+mod ram_search;
 mod retro_context;
 mod retro_perf;
+mod rumble;
+mod sensor;
+mod speed;
 mod tools;
 
 //arquivo principal!
 pub mod retro_ab;
 
 pub mod core;
+pub mod core_info;
 pub mod erro_handle;
 pub mod graphic_api;
 pub mod paths;
 pub mod system;
 pub mod test_tools;
+pub mod vfs;
 
+pub use managers::cheat_manager;
+pub use managers::movie_manager;
 pub use managers::option_manager;
+pub use managers::remap_manager;
 
 pub use managers::args_manager;
 
@@ -0,0 +1,509 @@
+use crate::constants::CHEAT_EXTENSION_FILE;
+use crate::erro_handle::{ErroHandle, RetroLogLevel};
+use crate::memory::Memory;
+use crate::retro_sys::LibretroRaw;
+use crate::tools::ffi_tools::make_c_string;
+use log::{debug, warn};
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::RwLock,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CheatHandler {
+    #[doc = "O código e enviado ao núcleo com retro_cheat_set."]
+    Core,
+    #[doc = "O frontend escreve `value` em `address` a cada frame."]
+    Ram,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cheat {
+    pub desc: String,
+    pub code: String,
+    pub enabled: bool,
+    pub handler: CheatHandler,
+    #[doc = "Endereço emulado usado pelos cheats [CheatHandler::Ram]."]
+    pub address: usize,
+    #[doc = "Se verdadeiro `address` e um índice dentro de RETRO_MEMORY_SYSTEM_RAM, como os da busca na RAM."]
+    pub system_ram: bool,
+    pub value: u32,
+    #[doc = "Tamanho do valor em bytes: 1, 2 ou 4."]
+    pub size: u32,
+    pub big_endian: bool,
+}
+
+impl Cheat {
+    pub fn new_core(desc: &str, code: &str) -> Cheat {
+        Cheat {
+            desc: desc.to_string(),
+            code: code.to_string(),
+            enabled: false,
+            handler: CheatHandler::Core,
+            address: 0,
+            system_ram: false,
+            value: 0,
+            size: 1,
+            big_endian: false,
+        }
+    }
+
+    pub fn new_ram(desc: &str, address: usize, value: u32, size: u32) -> Cheat {
+        Cheat {
+            desc: desc.to_string(),
+            code: String::new(),
+            enabled: false,
+            handler: CheatHandler::Ram,
+            address,
+            system_ram: false,
+            value,
+            size,
+            big_endian: false,
+        }
+    }
+
+    fn get_bytes(&self) -> Vec<u8> {
+        let bytes = if self.big_endian {
+            self.value.to_be_bytes().to_vec()
+        } else {
+            self.value.to_le_bytes().to_vec()
+        };
+
+        match (self.size, self.big_endian) {
+            (1, false) => bytes[..1].to_vec(),
+            (1, true) => bytes[3..].to_vec(),
+            (2, false) => bytes[..2].to_vec(),
+            (2, true) => bytes[2..].to_vec(),
+            _ => bytes,
+        }
+    }
+}
+
+#[doc = "
+    # Cheats
+
+    Lê e salva arquivos .cht no formato do RetroArch. Os cheats de cada jogo ficam em
+    `cheats/<núcleo>/<rom>.cht`.
+"]
+#[derive(Debug, Default)]
+pub struct CheatManager {
+    pub core_dir: PathBuf,
+    pub game_file_path: RwLock<Option<PathBuf>>,
+    pub cheats: RwLock<Vec<Cheat>>,
+}
+
+impl CheatManager {
+    pub fn new(cheats_path: &str, library_name: String) -> CheatManager {
+        CheatManager {
+            core_dir: PathBuf::from(cheats_path).join(library_name),
+            game_file_path: RwLock::new(None),
+            cheats: RwLock::new(Vec::new()),
+        }
+    }
+
+    pub fn get_cheats(&self) -> Vec<Cheat> {
+        self.cheats.read().unwrap().clone()
+    }
+
+    pub fn add_cheat(&self, cheat: Cheat) -> usize {
+        let mut cheats = self.cheats.write().unwrap();
+        cheats.push(cheat);
+
+        cheats.len() - 1
+    }
+
+    pub fn remove_cheat(&self, index: usize) -> Result<Cheat, ErroHandle> {
+        let mut cheats = self.cheats.write().unwrap();
+
+        if index >= cheats.len() {
+            return Err(invalid_index(index));
+        }
+
+        Ok(cheats.remove(index))
+    }
+
+    pub fn set_enabled(&self, index: usize, enabled: bool) -> Result<(), ErroHandle> {
+        match self.cheats.write().unwrap().get_mut(index) {
+            Some(cheat) => {
+                cheat.enabled = enabled;
+                Ok(())
+            }
+            None => Err(invalid_index(index)),
+        }
+    }
+
+    pub fn clear(&self) {
+        self.cheats.write().unwrap().clear();
+    }
+
+    #[doc = "Envia todos os cheats [CheatHandler::Core] para o núcleo, deve ser chamado depois de reset e load_state."]
+    pub fn apply_core_cheats(&self, raw: &LibretroRaw) {
+        unsafe { raw.retro_cheat_reset() };
+
+        for (index, cheat) in self.cheats.read().unwrap().iter().enumerate() {
+            if !cheat.enabled || cheat.handler != CheatHandler::Core {
+                continue;
+            }
+
+            if let Ok(code) = make_c_string(&cheat.code) {
+                unsafe { raw.retro_cheat_set(index as u32, true, code.as_ptr()) };
+            }
+        }
+    }
+
+    #[doc = "Deve ser chamado a cada frame antes de retro_run."]
+    pub fn apply_ram_cheats(&self, memory: &Memory) {
+        for cheat in self.cheats.read().unwrap().iter() {
+            if !cheat.enabled || cheat.handler != CheatHandler::Ram {
+                continue;
+            }
+
+            let result = if cheat.system_ram {
+                memory.write_system_ram(cheat.address, &cheat.get_bytes())
+            } else {
+                memory.write_bytes(cheat.address, &cheat.get_bytes())
+            };
+
+            if let Err(e) = result {
+                debug!("cheat {} ignorado: {}", cheat.desc, e.message);
+            }
+        }
+    }
+
+    //===============================================
+    //=================arquivos======================
+    //===============================================
+
+    #[doc = "Carrega os cheats salvos para a rom, se nao existir nenhum a lista fica vazia."]
+    pub fn load_game_cheats(&self, rom_name: Option<&str>) {
+        //set_extension cortaria nomes com ponto, ex: "Dr. Mario"
+        let game_file_path = rom_name.map(|rom_name| {
+            self.core_dir
+                .join(format!("{rom_name}{CHEAT_EXTENSION_FILE}"))
+        });
+
+        *self.game_file_path.write().unwrap() = game_file_path.clone();
+        self.clear();
+
+        if let Some(path) = game_file_path {
+            if path.exists() {
+                let _ = self.load_file(&path);
+            }
+        }
+    }
+
+    #[doc = "Substitui os cheats atuais pelos do arquivo, útil para importar os .cht do RetroArch."]
+    pub fn load_file(&self, path: &Path) -> Result<(), ErroHandle> {
+        let buf = fs::read_to_string(path).map_err(|e| ErroHandle {
+            level: RetroLogLevel::RETRO_LOG_ERROR,
+            message: e.to_string(),
+        })?;
+
+        *self.cheats.write().unwrap() = parse_cht(&buf);
+
+        Ok(())
+    }
+
+    pub fn save_cheats(&self) -> Result<(), ErroHandle> {
+        let file_path = match &*self.game_file_path.read().unwrap() {
+            Some(path) => path.clone(),
+            None => {
+                return Err(ErroHandle {
+                    level: RetroLogLevel::RETRO_LOG_WARN,
+                    message: "Para salva os cheats uma rom deve esta carregada".to_string(),
+                })
+            }
+        };
+
+        if !self.core_dir.exists() && fs::create_dir_all(&self.core_dir).is_err() {
+            return Err(ErroHandle {
+                level: RetroLogLevel::RETRO_LOG_ERROR,
+                message: "Não foi possível criar a pasta dos cheats".to_string(),
+            });
+        }
+
+        fs::write(file_path, cheats_to_string(&self.cheats.read().unwrap())).map_err(|e| {
+            ErroHandle {
+                level: RetroLogLevel::RETRO_LOG_ERROR,
+                message: e.to_string(),
+            }
+        })
+    }
+}
+
+fn invalid_index(index: usize) -> ErroHandle {
+    ErroHandle {
+        level: RetroLogLevel::RETRO_LOG_WARN,
+        message: "Nao existe nenhum cheat no índice ".to_string() + &index.to_string(),
+    }
+}
+
+// memory_search_size do RetroArch: 3 = 8 bits, 4 = 16 bits e 5 = 32 bits
+fn size_from_search_size(search_size: u32) -> u32 {
+    match search_size {
+        4 => 2,
+        5 => 4,
+        _ => 1,
+    }
+}
+
+fn search_size_from_size(size: u32) -> u32 {
+    match size {
+        2 => 4,
+        4 => 5,
+        _ => 3,
+    }
+}
+
+// formato do arquivo:
+// cheats = 1
+//
+// cheat0_desc = "Vidas infinitas"
+// cheat0_code = "7E0DBE:05"
+// cheat0_enable = true
+// cheat0_handler = 1
+// cheat0_address = 3518
+// cheat0_value = 5
+// cheat0_memory_search_size = 3
+// cheat0_big_endian = false
+// cheat0_cheat_type = 1
+// cheat0_system_ram = false
+//
+// as chaves repeat_* e rumble_* do RetroArch nao sao suportadas
+fn cheats_to_string(cheats: &[Cheat]) -> String {
+    let mut buf = format!("cheats = {}\n", cheats.len());
+
+    for (index, cheat) in cheats.iter().enumerate() {
+        let handler = match cheat.handler {
+            CheatHandler::Core => 0,
+            CheatHandler::Ram => 1,
+        };
+
+        buf += &format!(
+            "\ncheat{index}_desc = \"{}\"\n\
+             cheat{index}_code = \"{}\"\n\
+             cheat{index}_enable = {}\n\
+             cheat{index}_handler = {handler}\n\
+             cheat{index}_address = {}\n\
+             cheat{index}_value = {}\n\
+             cheat{index}_memory_search_size = {}\n\
+             cheat{index}_big_endian = {}\n\
+             cheat{index}_cheat_type = {CHEAT_TYPE_SET_TO_VALUE}\n\
+             cheat{index}_system_ram = {}\n",
+            escape_value(&cheat.desc),
+            escape_value(&cheat.code),
+            cheat.enabled,
+            cheat.address,
+            cheat.value,
+            search_size_from_size(cheat.size),
+            cheat.big_endian,
+            cheat.system_ram,
+        );
+    }
+
+    buf
+}
+
+// cheat_type do RetroArch, os outros tipos (aumentar, diminuir, run next if...) nao sao suportados
+const CHEAT_TYPE_DISABLED: u32 = 0;
+const CHEAT_TYPE_SET_TO_VALUE: u32 = 1;
+
+fn escape_value(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r")
+}
+
+fn unescape_value(value: &str) -> String {
+    let value = value.trim();
+    let value = match value.strip_prefix('"').and_then(|v| v.strip_suffix('"')) {
+        Some(value) => value,
+        None => value,
+    };
+
+    let mut buf = String::with_capacity(value.len());
+    let mut chars = value.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            buf.push(c);
+            continue;
+        }
+
+        match chars.next() {
+            Some('n') => buf.push('\n'),
+            Some('r') => buf.push('\r'),
+            Some(c) => buf.push(c),
+            None => buf.push('\\'),
+        }
+    }
+
+    buf
+}
+
+fn parse_cht(buf: &str) -> Vec<Cheat> {
+    let values: HashMap<&str, String> = buf
+        .lines()
+        .filter_map(|line| {
+            let (key, value) = line.split_once('=')?;
+            Some((key.trim(), unescape_value(value)))
+        })
+        .collect();
+
+    let count: usize = values
+        .get("cheats")
+        .and_then(|count| count.parse().ok())
+        .unwrap_or(0);
+
+    (0..count)
+        .filter_map(|index| {
+            let get = |name: &str| {
+                values
+                    .get(format!("cheat{index}_{name}").as_str())
+                    .map(|value| value.as_str())
+                    .unwrap_or_default()
+            };
+            let get_number = |name: &str| get(name).parse::<u32>().unwrap_or(0);
+
+            let handler = if get_number("handler") == 1 {
+                CheatHandler::Ram
+            } else {
+                CheatHandler::Core
+            };
+
+            //sem cheat_type o RetroArch assume que o valor deve ser escrito
+            let cheat_type = get("cheat_type")
+                .parse::<u32>()
+                .unwrap_or(CHEAT_TYPE_SET_TO_VALUE);
+            let repeat_count = get("repeat_count").parse::<u32>().unwrap_or(1);
+
+            if handler == CheatHandler::Ram
+                && (cheat_type > CHEAT_TYPE_SET_TO_VALUE || repeat_count > 1)
+            {
+                warn!(
+                    "cheat {} ignorado: cheat_type {} e repeat_count {} nao sao suportados",
+                    get("desc"),
+                    cheat_type,
+                    repeat_count
+                );
+                return None;
+            }
+
+            Some(Cheat {
+                desc: get("desc").to_string(),
+                code: get("code").to_string(),
+                enabled: get("enable") == "true" && cheat_type != CHEAT_TYPE_DISABLED,
+                handler,
+                address: get_number("address") as usize,
+                value: get_number("value"),
+                size: size_from_search_size(get_number("memory_search_size")),
+                big_endian: get("big_endian") == "true",
+                system_ram: get("system_ram") == "true",
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test_cheat_manager {
+    use super::{parse_cht, Cheat, CheatHandler, CheatManager};
+    use crate::memory::Memory;
+    use std::path::Path;
+
+    #[test]
+    fn save_and_reload() {
+        let cheats = CheatManager::new("retro_out_test/cheats", "test".to_string());
+        cheats.load_game_cheats(Some("rom"));
+        cheats.clear();
+
+        cheats.add_cheat(Cheat::new_core("Vidas \"infinitas\"\n\\", "7E0DBE:05"));
+
+        let mut ram_cheat = Cheat::new_ram("Dinheiro", 0x10, 0x1234, 2);
+        ram_cheat.big_endian = true;
+        ram_cheat.system_ram = true;
+        let index = cheats.add_cheat(ram_cheat);
+        cheats.set_enabled(index, true).unwrap();
+
+        let expected = cheats.get_cheats();
+
+        cheats.save_cheats().unwrap();
+        cheats.load_game_cheats(Some("rom"));
+
+        assert_eq!(cheats.get_cheats(), expected);
+        assert_eq!(cheats.get_cheats()[1].handler, CheatHandler::Ram);
+        assert!(cheats.set_enabled(5, true).is_err());
+
+        cheats.load_game_cheats(Some("Dr. Mario"));
+        assert_eq!(
+            cheats.game_file_path.read().unwrap().as_deref(),
+            Some(Path::new("retro_out_test/cheats/test/Dr. Mario.cht"))
+        );
+    }
+
+    #[test]
+    fn cheat_types() {
+        let cheats = parse_cht(
+            "cheats = 4\n\
+             cheat0_desc = \"escrever\"\n\
+             cheat0_enable = true\n\
+             cheat0_handler = 1\n\
+             cheat0_cheat_type = 1\n\
+             cheat1_desc = \"aumentar\"\n\
+             cheat1_enable = true\n\
+             cheat1_handler = 1\n\
+             cheat1_cheat_type = 2\n\
+             cheat2_desc = \"repetir\"\n\
+             cheat2_enable = true\n\
+             cheat2_handler = 1\n\
+             cheat2_repeat_count = 4\n\
+             cheat3_desc = \"desativado\"\n\
+             cheat3_enable = true\n\
+             cheat3_handler = 1\n\
+             cheat3_cheat_type = 0\n",
+        );
+
+        assert_eq!(cheats.len(), 2);
+        assert_eq!(cheats[0].desc, "escrever");
+        assert!(cheats[0].enabled);
+        assert_eq!(cheats[1].desc, "desativado");
+        assert!(!cheats[1].enabled);
+    }
+
+    #[test]
+    fn ram_cheats() {
+        let mut ram = vec![0u8; 8];
+        let memory = Memory::default();
+        memory.set_system_ram(ram.as_mut_ptr(), ram.len());
+
+        let cheats = CheatManager::new("retro_out_test/cheats", "test".to_string());
+
+        let mut big_endian = Cheat::new_ram("16 bits", 0, 0x1234, 2);
+        big_endian.enabled = true;
+        big_endian.big_endian = true;
+        cheats.add_cheat(big_endian);
+
+        let mut byte = Cheat::new_ram("8 bits", 4, 0xAB, 1);
+        byte.enabled = true;
+        cheats.add_cheat(byte);
+
+        let mut big_endian_byte = Cheat::new_ram("8 bits big endian", 5, 0xCD, 1);
+        big_endian_byte.enabled = true;
+        big_endian_byte.big_endian = true;
+        cheats.add_cheat(big_endian_byte);
+
+        cheats.add_cheat(Cheat::new_ram("desativado", 6, 0xFF, 1));
+
+        //endereços fora da memoria sao ignorados
+        let mut invalid = Cheat::new_ram("invalido", 100, 1, 1);
+        invalid.enabled = true;
+        cheats.add_cheat(invalid);
+
+        cheats.apply_ram_cheats(&memory);
+
+        assert_eq!(ram, [0x12, 0x34, 0, 0, 0xAB, 0xCD, 0, 0]);
+    }
+}
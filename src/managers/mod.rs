@@ -1,2 +1,5 @@
 pub mod args_manager;
+pub mod cheat_manager;
+pub mod movie_manager;
 pub mod option_manager;
+pub mod remap_manager;
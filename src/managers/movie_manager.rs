@@ -0,0 +1,422 @@
+use crate::erro_handle::{ErroHandle, RetroLogLevel};
+use std::{
+    fs::File,
+    io::{Read, Write},
+    path::Path,
+    sync::RwLock,
+};
+
+const MOVIE_MAGIC: &[u8; 4] = b"RABM";
+const MOVIE_VERSION: u32 = 1;
+//port, device, index e id em u32 mais o valor em i16
+const INPUT_RECORD_SIZE: usize = 4 * 4 + 2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InputRecord {
+    pub port: u32,
+    pub device: u32,
+    pub index: u32,
+    pub id: u32,
+    pub value: i16,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub struct Movie {
+    pub core_name: String,
+    pub core_version: String,
+    pub rom_crc32: u32,
+    #[doc = "Estado de onde a gravação começou, se for None a gravação começou no power-on."]
+    pub save_state: Option<Vec<u8>>,
+    #[doc = "Todos os valores entregues ao núcleo, um Vec para cada input_poll."]
+    pub frames: Vec<Vec<InputRecord>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MovieState {
+    Idle,
+    Recording,
+    Playing,
+}
+
+fn movie_error(message: &str) -> ErroHandle {
+    ErroHandle {
+        level: RetroLogLevel::RETRO_LOG_ERROR,
+        message: message.to_string(),
+    }
+}
+
+fn read_u32(buf: &[u8], pos: &mut usize) -> Result<u32, ErroHandle> {
+    let bytes = buf
+        .get(*pos..*pos + 4)
+        .ok_or_else(|| movie_error("O arquivo do movie esta incompleto"))?;
+    *pos += 4;
+
+    Ok(u32::from_le_bytes(bytes.try_into().unwrap()))
+}
+
+fn read_bytes<'a>(buf: &'a [u8], pos: &mut usize) -> Result<&'a [u8], ErroHandle> {
+    let size = read_u32(buf, pos)? as usize;
+    let bytes = buf
+        .get(*pos..*pos + size)
+        .ok_or_else(|| movie_error("O arquivo do movie esta incompleto"))?;
+    *pos += size;
+
+    Ok(bytes)
+}
+
+fn write_bytes(buf: &mut Vec<u8>, bytes: &[u8]) {
+    buf.extend_from_slice(&(bytes.len() as u32).to_le_bytes());
+    buf.extend_from_slice(bytes);
+}
+
+impl Movie {
+    pub fn frame_count(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buf = Vec::new();
+
+        buf.extend_from_slice(MOVIE_MAGIC);
+        buf.extend_from_slice(&MOVIE_VERSION.to_le_bytes());
+        buf.extend_from_slice(&(self.frames.len() as u32).to_le_bytes());
+        write_bytes(&mut buf, self.core_name.as_bytes());
+        write_bytes(&mut buf, self.core_version.as_bytes());
+        buf.extend_from_slice(&self.rom_crc32.to_le_bytes());
+
+        match &self.save_state {
+            Some(state) => {
+                buf.push(1);
+                write_bytes(&mut buf, state);
+            }
+            None => buf.push(0),
+        }
+
+        for frame in &self.frames {
+            buf.extend_from_slice(&(frame.len() as u32).to_le_bytes());
+
+            for record in frame {
+                buf.extend_from_slice(&record.port.to_le_bytes());
+                buf.extend_from_slice(&record.device.to_le_bytes());
+                buf.extend_from_slice(&record.index.to_le_bytes());
+                buf.extend_from_slice(&record.id.to_le_bytes());
+                buf.extend_from_slice(&record.value.to_le_bytes());
+            }
+        }
+
+        buf
+    }
+
+    pub fn from_bytes(buf: &[u8]) -> Result<Movie, ErroHandle> {
+        if buf.len() < 8 || &buf[0..4] != MOVIE_MAGIC {
+            return Err(movie_error("O arquivo escolhido nao e um movie valido"));
+        }
+
+        let mut pos = 4;
+
+        if read_u32(buf, &mut pos)? != MOVIE_VERSION {
+            return Err(movie_error("A versão do movie nao e suportada"));
+        }
+
+        let frame_count = read_u32(buf, &mut pos)? as usize;
+        let core_name = String::from_utf8_lossy(read_bytes(buf, &mut pos)?).to_string();
+        let core_version = String::from_utf8_lossy(read_bytes(buf, &mut pos)?).to_string();
+        let rom_crc32 = read_u32(buf, &mut pos)?;
+
+        let has_state = *buf
+            .get(pos)
+            .ok_or_else(|| movie_error("O arquivo do movie esta incompleto"))?;
+        pos += 1;
+
+        let save_state = if has_state == 1 {
+            Some(read_bytes(buf, &mut pos)?.to_vec())
+        } else {
+            None
+        };
+
+        //as quantidades vem do arquivo, a reserva nunca passa do que ainda pode ser lido
+        let mut frames = Vec::with_capacity(frame_count.min((buf.len() - pos) / 4));
+
+        for _ in 0..frame_count {
+            let num_records = read_u32(buf, &mut pos)?;
+            let mut frame = Vec::with_capacity(
+                (num_records as usize).min((buf.len() - pos) / INPUT_RECORD_SIZE),
+            );
+
+            for _ in 0..num_records {
+                let port = read_u32(buf, &mut pos)?;
+                let device = read_u32(buf, &mut pos)?;
+                let index = read_u32(buf, &mut pos)?;
+                let id = read_u32(buf, &mut pos)?;
+                let value = buf
+                    .get(pos..pos + 2)
+                    .ok_or_else(|| movie_error("O arquivo do movie esta incompleto"))?;
+                pos += 2;
+
+                frame.push(InputRecord {
+                    port,
+                    device,
+                    index,
+                    id,
+                    value: i16::from_le_bytes(value.try_into().unwrap()),
+                });
+            }
+
+            frames.push(frame);
+        }
+
+        Ok(Movie {
+            core_name,
+            core_version,
+            rom_crc32,
+            save_state,
+            frames,
+        })
+    }
+
+    pub fn write_to_file(&self, path: &Path) -> Result<(), ErroHandle> {
+        let mut file = File::create(path)
+            .map_err(|_| movie_error("Nao foi possível criar o arquivo do movie"))?;
+
+        file.write_all(&self.to_bytes())
+            .map_err(|_| movie_error("Nao foi possível salva o movie"))
+    }
+
+    pub fn read_from_file(path: &Path) -> Result<Movie, ErroHandle> {
+        let mut file = File::open(path)
+            .map_err(|_| movie_error("Nao foi possível abrir o arquivo do movie"))?;
+
+        let mut buf = Vec::new();
+        file.read_to_end(&mut buf)
+            .map_err(|_| movie_error("Nao foi possível ler o arquivo do movie"))?;
+
+        Movie::from_bytes(&buf)
+    }
+}
+
+#[derive(Debug)]
+pub struct MovieManager {
+    pub state: RwLock<MovieState>,
+    movie: RwLock<Movie>,
+    #[doc = "Frame atual da gravação ou reprodução, None ate o primeiro input_poll."]
+    frame: RwLock<Option<usize>>,
+}
+
+impl Default for MovieManager {
+    fn default() -> Self {
+        MovieManager {
+            state: RwLock::new(MovieState::Idle),
+            movie: RwLock::new(Movie::default()),
+            frame: RwLock::new(None),
+        }
+    }
+}
+
+impl MovieManager {
+    pub fn get_state(&self) -> MovieState {
+        *self.state.read().unwrap()
+    }
+
+    pub fn start_recording(&self, movie: Movie) {
+        *self.movie.write().unwrap() = Movie {
+            frames: Vec::new(),
+            ..movie
+        };
+        *self.frame.write().unwrap() = None;
+        *self.state.write().unwrap() = MovieState::Recording;
+    }
+
+    pub fn start_playback(&self, movie: Movie) {
+        *self.movie.write().unwrap() = movie;
+        *self.frame.write().unwrap() = None;
+        *self.state.write().unwrap() = MovieState::Playing;
+    }
+
+    #[doc = "Encerra a gravação ou reprodução e devolve o movie usado."]
+    pub fn stop(&self) -> Movie {
+        *self.state.write().unwrap() = MovieState::Idle;
+        *self.frame.write().unwrap() = None;
+
+        std::mem::take(&mut *self.movie.write().unwrap())
+    }
+
+    pub fn on_input_poll(&self) {
+        match self.get_state() {
+            MovieState::Recording => {
+                let mut frame = self.frame.write().unwrap();
+                let mut movie = self.movie.write().unwrap();
+
+                //inputs lidos antes do primeiro poll ja criaram o frame 0
+                if frame.is_some() || movie.frames.is_empty() {
+                    movie.frames.push(Vec::new());
+                }
+
+                *frame = Some(movie.frames.len() - 1);
+            }
+            MovieState::Playing => {
+                let mut frame = self.frame.write().unwrap();
+                let next = frame.map_or(0, |f| f + 1);
+
+                if next >= self.movie.read().unwrap().frame_count() {
+                    // o movie acabou, a partir daqui o frontend volta a controlar o núcleo
+                    *frame = None;
+                    *self.state.write().unwrap() = MovieState::Idle;
+                } else {
+                    *frame = Some(next);
+                }
+            }
+            MovieState::Idle => {}
+        }
+    }
+
+    pub fn input_state<F: FnOnce() -> i16>(
+        &self,
+        port: u32,
+        device: u32,
+        index: u32,
+        id: u32,
+        live: F,
+    ) -> i16 {
+        let is_record = |r: &&InputRecord| {
+            r.port == port && r.device == device && r.index == index && r.id == id
+        };
+
+        match self.get_state() {
+            MovieState::Idle => live(),
+            MovieState::Playing => {
+                //antes do primeiro poll a leitura e a mesma gravada no frame 0
+                let frame = self.frame.read().unwrap().unwrap_or(0);
+
+                self.movie
+                    .read()
+                    .unwrap()
+                    .frames
+                    .get(frame)
+                    .and_then(|frame| frame.iter().find(is_record))
+                    .map_or(0, |r| r.value)
+            }
+            MovieState::Recording => {
+                let value = live();
+                let mut movie = self.movie.write().unwrap();
+
+                if movie.frames.is_empty() {
+                    movie.frames.push(Vec::new());
+                }
+
+                let frame = movie.frames.last_mut().unwrap();
+
+                if !frame.iter().any(|r| is_record(&r)) {
+                    frame.push(InputRecord {
+                        port,
+                        device,
+                        index,
+                        id,
+                        value,
+                    });
+                }
+
+                value
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_movie_manager {
+    use super::{InputRecord, Movie, MovieManager, MovieState};
+
+    fn get_movie() -> Movie {
+        Movie {
+            core_name: "Snes9x".to_string(),
+            core_version: "1.62.3 46f8a6b".to_string(),
+            rom_crc32: 0xdeadbeef,
+            save_state: Some(vec![1, 2, 3]),
+            frames: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn record_and_play() {
+        let movie_manager = MovieManager::default();
+        movie_manager.start_recording(get_movie());
+
+        for frame in 0..3 {
+            movie_manager.on_input_poll();
+            movie_manager.input_state(0, 1, 0, 8, || frame);
+            movie_manager.input_state(0, 1, 0, 8, || 100);
+        }
+
+        let movie = movie_manager.stop();
+
+        assert_eq!(movie.frame_count(), 3);
+        assert_eq!(
+            movie.frames[2],
+            vec![InputRecord {
+                port: 0,
+                device: 1,
+                index: 0,
+                id: 8,
+                value: 2
+            }]
+        );
+
+        movie_manager.start_playback(movie);
+
+        for frame in 0..3 {
+            movie_manager.on_input_poll();
+            assert_eq!(movie_manager.input_state(0, 1, 0, 8, || 100), frame);
+            assert_eq!(movie_manager.input_state(1, 1, 0, 8, || 100), 0);
+        }
+
+        movie_manager.on_input_poll();
+
+        assert_eq!(movie_manager.get_state(), MovieState::Idle);
+        assert_eq!(movie_manager.input_state(0, 1, 0, 8, || 100), 100);
+    }
+
+    #[test]
+    fn input_before_first_poll() {
+        let movie_manager = MovieManager::default();
+        movie_manager.start_recording(get_movie());
+
+        movie_manager.input_state(0, 1, 0, 8, || 7);
+
+        for _ in 0..2 {
+            movie_manager.on_input_poll();
+            movie_manager.input_state(0, 1, 0, 8, || 7);
+        }
+
+        let movie = movie_manager.stop();
+        assert_eq!(movie.frame_count(), 2);
+
+        movie_manager.start_playback(movie);
+        assert_eq!(movie_manager.input_state(0, 1, 0, 8, || 100), 7);
+    }
+
+    #[test]
+    fn movie_bytes() {
+        let mut movie = get_movie();
+        movie.frames = vec![
+            vec![],
+            vec![InputRecord {
+                port: 1,
+                device: 5,
+                index: 0,
+                id: 1,
+                value: -0x7fff,
+            }],
+        ];
+
+        assert_eq!(Movie::from_bytes(&movie.to_bytes()).unwrap(), movie);
+
+        movie.save_state = None;
+
+        assert_eq!(Movie::from_bytes(&movie.to_bytes()).unwrap(), movie);
+        assert!(Movie::from_bytes(b"RABX").is_err());
+
+        //um arquivo truncado com uma quantidade enorme de frames nao pode abortar na alocação
+        let mut buf = movie.to_bytes();
+        buf[8..12].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(Movie::from_bytes(&buf).is_err());
+    }
+}
@@ -0,0 +1,634 @@
+use crate::constants::CORE_REMAP_EXTENSION_FILE;
+use crate::erro_handle::{ErroHandle, RetroLogLevel};
+use crate::retro_sys::{
+    RETRO_DEVICE_ANALOG, RETRO_DEVICE_ID_JOYPAD_MASK, RETRO_DEVICE_ID_JOYPAD_R3,
+    RETRO_DEVICE_JOYPAD,
+};
+use std::{
+    fs::{self, File},
+    io::{Read, Write},
+    path::PathBuf,
+    sync::RwLock,
+};
+
+pub type InputStateFn = fn(port: i16, device: i16, index: i16, id: i16) -> i16;
+
+const MAX_ANALOG_VALUE: i16 = 0x7fff;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ButtonRemap {
+    #[doc = "Botão que o núcleo vai consultar."]
+    pub core: u32,
+    #[doc = "Botão que sera lido do frontend no lugar de [ButtonRemap::core]."]
+    pub frontend: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AnalogToDigital {
+    #[doc = "Botão que o núcleo vai consultar."]
+    pub button: u32,
+    #[doc = "RETRO_DEVICE_INDEX_ANALOG_LEFT ou RETRO_DEVICE_INDEX_ANALOG_RIGHT."]
+    pub index: u32,
+    #[doc = "RETRO_DEVICE_ID_ANALOG_X ou RETRO_DEVICE_ID_ANALOG_Y."]
+    pub axis: u32,
+    #[doc = "Se verdadeiro o botão e pressionado no lado positivo do eixo."]
+    pub positive: bool,
+    pub threshold: i16,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DigitalToAnalog {
+    #[doc = "RETRO_DEVICE_INDEX_ANALOG_LEFT ou RETRO_DEVICE_INDEX_ANALOG_RIGHT."]
+    pub index: u32,
+    #[doc = "RETRO_DEVICE_ID_ANALOG_X ou RETRO_DEVICE_ID_ANALOG_Y."]
+    pub axis: u32,
+    #[doc = "Botão do frontend que empurra o eixo para o lado negativo."]
+    pub negative: u32,
+    #[doc = "Botão do frontend que empurra o eixo para o lado positivo."]
+    pub positive: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Turbo {
+    pub button: u32,
+    #[doc = "Duração de um ciclo completo (pressionado + solto) em frames."]
+    pub period: u32,
+}
+
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct PortRemap {
+    pub port: u32,
+    pub buttons: Vec<ButtonRemap>,
+    pub analog_to_digital: Vec<AnalogToDigital>,
+    pub digital_to_analog: Vec<DigitalToAnalog>,
+    pub turbo: Vec<Turbo>,
+}
+
+impl PortRemap {
+    fn new(port: u32) -> Self {
+        PortRemap {
+            port,
+            ..Default::default()
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.buttons.is_empty()
+            && self.analog_to_digital.is_empty()
+            && self.digital_to_analog.is_empty()
+            && self.turbo.is_empty()
+    }
+}
+
+#[derive(Default, Debug)]
+pub struct RemapManager {
+    pub core_file_path: RwLock<PathBuf>,
+    pub game_file_path: RwLock<Option<PathBuf>>,
+    pub ports: RwLock<Vec<PortRemap>>,
+    frame: RwLock<u64>,
+}
+
+impl RemapManager {
+    pub fn new(opt_path: &str, library_name: String) -> RemapManager {
+        let core_file_path = PathBuf::from(opt_path).join(library_name + CORE_REMAP_EXTENSION_FILE);
+
+        RemapManager {
+            core_file_path: RwLock::new(core_file_path),
+            game_file_path: RwLock::new(None),
+            ports: RwLock::new(Vec::new()),
+            frame: RwLock::new(0),
+        }
+    }
+
+    fn change_port<F: FnOnce(&mut PortRemap)>(&self, port: u32, f: F) {
+        let mut ports = self.ports.write().unwrap();
+
+        match ports.iter_mut().find(|p| p.port == port) {
+            Some(port_remap) => f(port_remap),
+            None => {
+                let mut port_remap = PortRemap::new(port);
+                f(&mut port_remap);
+                ports.push(port_remap);
+            }
+        }
+    }
+
+    pub fn set_button(&self, port: u32, core: u32, frontend: u32) {
+        self.change_port(port, |p| {
+            p.buttons.retain(|b| b.core != core);
+
+            if core != frontend {
+                p.buttons.push(ButtonRemap { core, frontend });
+            }
+        });
+    }
+
+    pub fn set_analog_to_digital(&self, port: u32, remap: AnalogToDigital) {
+        self.change_port(port, |p| {
+            p.analog_to_digital.retain(|a| a.button != remap.button);
+            p.analog_to_digital.push(remap);
+        });
+    }
+
+    pub fn set_digital_to_analog(&self, port: u32, remap: DigitalToAnalog) {
+        self.change_port(port, |p| {
+            p.digital_to_analog
+                .retain(|d| d.index != remap.index || d.axis != remap.axis);
+            p.digital_to_analog.push(remap);
+        });
+    }
+
+    #[doc = "Um período igual a 0 desativa o turbo do botão."]
+    pub fn set_turbo(&self, port: u32, button: u32, period: u32) {
+        self.change_port(port, |p| {
+            p.turbo.retain(|t| t.button != button);
+
+            if period != 0 {
+                p.turbo.push(Turbo { button, period });
+            }
+        });
+    }
+
+    pub fn clear_port(&self, port: u32) {
+        self.ports.write().unwrap().retain(|p| p.port != port);
+    }
+
+    pub fn clear(&self) {
+        self.ports.write().unwrap().clear();
+    }
+
+    pub fn get_port(&self, port: u32) -> Option<PortRemap> {
+        self.ports
+            .read()
+            .unwrap()
+            .iter()
+            .find(|p| p.port == port)
+            .cloned()
+    }
+
+    #[doc = "Deve ser chamado a cada input_poll, o turbo usa isso para contar os frames."]
+    pub fn on_input_poll(&self) {
+        *self.frame.write().unwrap() += 1;
+    }
+
+    pub fn input_state(
+        &self,
+        port: u32,
+        device: u32,
+        index: u32,
+        id: u32,
+        frontend: InputStateFn,
+    ) -> i16 {
+        let ports = self.ports.read().unwrap();
+
+        let port_remap = match ports.iter().find(|p| p.port == port) {
+            Some(port_remap) => port_remap,
+            None => return frontend(port as i16, device as i16, index as i16, id as i16),
+        };
+
+        match device {
+            RETRO_DEVICE_JOYPAD if id == RETRO_DEVICE_ID_JOYPAD_MASK => {
+                let mut mask = 0;
+
+                for button in 0..=RETRO_DEVICE_ID_JOYPAD_R3 {
+                    if self.button_state(port_remap, button, frontend) {
+                        mask |= 1 << button;
+                    }
+                }
+
+                mask
+            }
+            RETRO_DEVICE_JOYPAD => self.button_state(port_remap, id, frontend) as i16,
+            RETRO_DEVICE_ANALOG => {
+                for remap in &port_remap.digital_to_analog {
+                    if remap.index != index || remap.axis != id {
+                        continue;
+                    }
+
+                    let negative = self.frontend_button(port_remap, remap.negative, frontend);
+                    let positive = self.frontend_button(port_remap, remap.positive, frontend);
+
+                    let value =
+                        positive as i16 * MAX_ANALOG_VALUE - negative as i16 * MAX_ANALOG_VALUE;
+
+                    if value != 0 {
+                        return value;
+                    }
+                }
+
+                frontend(port as i16, device as i16, index as i16, id as i16)
+            }
+            _ => frontend(port as i16, device as i16, index as i16, id as i16),
+        }
+    }
+
+    fn frontend_button(&self, port_remap: &PortRemap, button: u32, frontend: InputStateFn) -> bool {
+        frontend(
+            port_remap.port as i16,
+            RETRO_DEVICE_JOYPAD as i16,
+            0,
+            button as i16,
+        ) != 0
+    }
+
+    fn button_state(&self, port_remap: &PortRemap, id: u32, frontend: InputStateFn) -> bool {
+        let source = match port_remap.buttons.iter().find(|b| b.core == id) {
+            Some(remap) => remap.frontend,
+            None => id,
+        };
+
+        let mut pressed = self.frontend_button(port_remap, source, frontend);
+
+        for remap in &port_remap.analog_to_digital {
+            if remap.button != id {
+                continue;
+            }
+
+            let value = frontend(
+                port_remap.port as i16,
+                RETRO_DEVICE_ANALOG as i16,
+                remap.index as i16,
+                remap.axis as i16,
+            );
+
+            pressed |= if remap.positive {
+                value >= remap.threshold
+            } else {
+                //o threshold vem do arquivo e pode ser i16::MIN
+                value <= remap.threshold.saturating_neg()
+            };
+        }
+
+        if pressed {
+            if let Some(turbo) = port_remap.turbo.iter().find(|t| t.button == id) {
+                let frame = *self.frame.read().unwrap();
+                let half = (turbo.period as u64 / 2).max(1);
+
+                pressed = frame % (half * 2) < half;
+            }
+        }
+
+        pressed
+    }
+
+    //===============================================
+    //=================arquivos======================
+    //===============================================
+
+    #[doc = "Carrega o remap do jogo se ele existir, se nao carrega o remap do núcleo."]
+    pub fn try_reload_remap(&self, rom_name: Option<&str>) {
+        let game_file_path = rom_name.map(|rom_name| {
+            let core_file_path = self.core_file_path.read().unwrap();
+
+            //set_extension cortaria nomes com ponto, ex: "Super Mario Bros. 3"
+            core_file_path
+                .with_extension("")
+                .join(format!("{rom_name}{CORE_REMAP_EXTENSION_FILE}"))
+        });
+
+        *self.game_file_path.write().unwrap() = game_file_path.clone();
+        self.clear();
+
+        if let Some(path) = game_file_path {
+            if path.exists() {
+                self.load_all_remaps_in_file(&path);
+                return;
+            }
+        }
+
+        let core_file_path = self.core_file_path.read().unwrap().clone();
+
+        if core_file_path.exists() {
+            self.load_all_remaps_in_file(&core_file_path);
+        }
+    }
+
+    pub fn save_remap(&self, per_game: bool) -> Result<(), ErroHandle> {
+        let file_path = if per_game {
+            match &*self.game_file_path.read().unwrap() {
+                Some(path) => path.clone(),
+                None => {
+                    return Err(ErroHandle {
+                        level: RetroLogLevel::RETRO_LOG_WARN,
+                        message: "Para salva um remap do jogo uma rom deve esta carregada"
+                            .to_string(),
+                    })
+                }
+            }
+        } else {
+            self.core_file_path.read().unwrap().clone()
+        };
+
+        if let Some(parent) = file_path.parent() {
+            if !parent.exists() && fs::create_dir_all(parent).is_err() {
+                return Err(ErroHandle {
+                    level: RetroLogLevel::RETRO_LOG_ERROR,
+                    message: "Não foi possível criar a pasta do remap".to_string(),
+                });
+            }
+        }
+
+        let mut file = match File::create(file_path) {
+            Ok(file) => file,
+            Err(e) => {
+                return Err(ErroHandle {
+                    level: RetroLogLevel::RETRO_LOG_ERROR,
+                    message: e.to_string(),
+                })
+            }
+        };
+
+        for port_remap in &*self.ports.read().unwrap() {
+            if port_remap.is_empty() {
+                continue;
+            }
+
+            file.write_all(remap_to_string(port_remap).as_bytes())
+                .map_err(|e| ErroHandle {
+                    level: RetroLogLevel::RETRO_LOG_ERROR,
+                    message: e.to_string(),
+                })?;
+        }
+
+        Ok(())
+    }
+
+    fn load_all_remaps_in_file(&self, file_path: &PathBuf) {
+        let mut buf = String::new();
+
+        match File::open(file_path) {
+            Ok(mut file) => {
+                if file.read_to_string(&mut buf).is_err() {
+                    return;
+                }
+            }
+            Err(_) => return,
+        }
+
+        for line in buf.lines() {
+            if let Some((port, line)) = parse_line(line) {
+                self.change_port(port, |p| match line {
+                    RemapLine::Button(remap) => p.buttons.push(remap),
+                    RemapLine::Turbo(turbo) => p.turbo.push(turbo),
+                    RemapLine::AnalogToDigital(remap) => p.analog_to_digital.push(remap),
+                    RemapLine::DigitalToAnalog(remap) => p.digital_to_analog.push(remap),
+                });
+            }
+        }
+    }
+}
+
+enum RemapLine {
+    Button(ButtonRemap),
+    Turbo(Turbo),
+    AnalogToDigital(AnalogToDigital),
+    DigitalToAnalog(DigitalToAnalog),
+}
+
+// formato de cada linha:
+// port0_button_8=0
+// port0_turbo_0=6
+// port0_analog_to_digital_4=0,1,-,16384
+// port0_digital_to_analog_0_0=6,7
+fn remap_to_string(port_remap: &PortRemap) -> String {
+    let mut buf = String::new();
+    let port = port_remap.port;
+
+    for b in &port_remap.buttons {
+        buf += &format!("port{port}_button_{}={}\n", b.core, b.frontend);
+    }
+
+    for t in &port_remap.turbo {
+        buf += &format!("port{port}_turbo_{}={}\n", t.button, t.period);
+    }
+
+    for a in &port_remap.analog_to_digital {
+        let direction = if a.positive { "+" } else { "-" };
+
+        buf += &format!(
+            "port{port}_analog_to_digital_{}={},{},{direction},{}\n",
+            a.button, a.index, a.axis, a.threshold
+        );
+    }
+
+    for d in &port_remap.digital_to_analog {
+        buf += &format!(
+            "port{port}_digital_to_analog_{}_{}={},{}\n",
+            d.index, d.axis, d.negative, d.positive
+        );
+    }
+
+    buf
+}
+
+fn parse_line(line: &str) -> Option<(u32, RemapLine)> {
+    let (key, value) = line.trim().split_once('=')?;
+    let (port, kind) = key.strip_prefix("port")?.split_once('_')?;
+    let port: u32 = port.parse().ok()?;
+    let values: Vec<&str> = value.split(',').map(|v| v.trim()).collect();
+
+    if let Some(core) = kind.strip_prefix("button_") {
+        return Some((
+            port,
+            RemapLine::Button(ButtonRemap {
+                core: core.parse().ok()?,
+                frontend: values.first()?.parse().ok()?,
+            }),
+        ));
+    }
+
+    if let Some(button) = kind.strip_prefix("turbo_") {
+        return Some((
+            port,
+            RemapLine::Turbo(Turbo {
+                button: button.parse().ok()?,
+                period: values.first()?.parse().ok()?,
+            }),
+        ));
+    }
+
+    if let Some(button) = kind.strip_prefix("analog_to_digital_") {
+        return Some((
+            port,
+            RemapLine::AnalogToDigital(AnalogToDigital {
+                button: button.parse().ok()?,
+                index: values.first()?.parse().ok()?,
+                axis: values.get(1)?.parse().ok()?,
+                positive: *values.get(2)? == "+",
+                threshold: values.get(3)?.parse().ok()?,
+            }),
+        ));
+    }
+
+    if let Some(index_axis) = kind.strip_prefix("digital_to_analog_") {
+        let (index, axis) = index_axis.split_once('_')?;
+
+        return Some((
+            port,
+            RemapLine::DigitalToAnalog(DigitalToAnalog {
+                index: index.parse().ok()?,
+                axis: axis.parse().ok()?,
+                negative: values.first()?.parse().ok()?,
+                positive: values.get(1)?.parse().ok()?,
+            }),
+        ));
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod test_remap_manager {
+    use super::{AnalogToDigital, DigitalToAnalog, RemapManager};
+    use crate::retro_sys::{
+        RETRO_DEVICE_ANALOG, RETRO_DEVICE_ID_ANALOG_X, RETRO_DEVICE_ID_JOYPAD_A,
+        RETRO_DEVICE_ID_JOYPAD_B, RETRO_DEVICE_ID_JOYPAD_LEFT, RETRO_DEVICE_ID_JOYPAD_MASK,
+        RETRO_DEVICE_ID_JOYPAD_RIGHT, RETRO_DEVICE_INDEX_ANALOG_LEFT, RETRO_DEVICE_JOYPAD,
+    };
+
+    // frontend falso: apenas o botão A e o direcional para a direita estão pressionados
+    // e o analógico esquerdo esta todo para a esquerda.
+    fn frontend(_port: i16, device: i16, index: i16, id: i16) -> i16 {
+        match device as u32 {
+            RETRO_DEVICE_JOYPAD => {
+                (id as u32 == RETRO_DEVICE_ID_JOYPAD_A || id as u32 == RETRO_DEVICE_ID_JOYPAD_RIGHT)
+                    as i16
+            }
+            RETRO_DEVICE_ANALOG
+                if index as u32 == RETRO_DEVICE_INDEX_ANALOG_LEFT
+                    && id as u32 == RETRO_DEVICE_ID_ANALOG_X =>
+            {
+                -0x7fff
+            }
+            _ => 0,
+        }
+    }
+
+    fn joypad(remaps: &RemapManager, id: u32) -> i16 {
+        remaps.input_state(0, RETRO_DEVICE_JOYPAD, 0, id, frontend)
+    }
+
+    #[test]
+    fn button_remap() {
+        let remaps = RemapManager::new("retro_out_test/opt", "test".to_string());
+
+        assert_eq!(joypad(&remaps, RETRO_DEVICE_ID_JOYPAD_B), 0);
+
+        remaps.set_button(0, RETRO_DEVICE_ID_JOYPAD_B, RETRO_DEVICE_ID_JOYPAD_A);
+
+        assert_eq!(joypad(&remaps, RETRO_DEVICE_ID_JOYPAD_B), 1);
+
+        let mask = joypad(&remaps, RETRO_DEVICE_ID_JOYPAD_MASK);
+
+        assert_eq!(
+            mask & (1 << RETRO_DEVICE_ID_JOYPAD_B),
+            1 << RETRO_DEVICE_ID_JOYPAD_B
+        );
+    }
+
+    #[test]
+    fn turbo() {
+        let remaps = RemapManager::new("retro_out_test/opt", "test".to_string());
+        remaps.set_turbo(0, RETRO_DEVICE_ID_JOYPAD_A, 4);
+
+        let mut states = Vec::new();
+
+        for _ in 0..8 {
+            remaps.on_input_poll();
+            states.push(joypad(&remaps, RETRO_DEVICE_ID_JOYPAD_A));
+        }
+
+        assert_eq!(states, vec![1, 0, 0, 1, 1, 0, 0, 1]);
+    }
+
+    #[test]
+    fn analog_to_digital() {
+        let remaps = RemapManager::new("retro_out_test/opt", "test".to_string());
+
+        remaps.set_analog_to_digital(
+            0,
+            AnalogToDigital {
+                button: RETRO_DEVICE_ID_JOYPAD_LEFT,
+                index: RETRO_DEVICE_INDEX_ANALOG_LEFT,
+                axis: RETRO_DEVICE_ID_ANALOG_X,
+                positive: false,
+                threshold: 0x4000,
+            },
+        );
+
+        assert_eq!(joypad(&remaps, RETRO_DEVICE_ID_JOYPAD_LEFT), 1);
+
+        //um arquivo editado a mao pode ter o menor valor de i16
+        remaps.set_analog_to_digital(
+            0,
+            AnalogToDigital {
+                button: RETRO_DEVICE_ID_JOYPAD_B,
+                index: RETRO_DEVICE_INDEX_ANALOG_LEFT,
+                axis: RETRO_DEVICE_ID_ANALOG_X,
+                positive: false,
+                threshold: i16::MIN,
+            },
+        );
+
+        assert_eq!(joypad(&remaps, RETRO_DEVICE_ID_JOYPAD_B), 1);
+    }
+
+    #[test]
+    fn digital_to_analog() {
+        let remaps = RemapManager::new("retro_out_test/opt", "test".to_string());
+
+        remaps.set_digital_to_analog(
+            0,
+            DigitalToAnalog {
+                index: RETRO_DEVICE_INDEX_ANALOG_LEFT,
+                axis: RETRO_DEVICE_ID_ANALOG_X,
+                negative: RETRO_DEVICE_ID_JOYPAD_LEFT,
+                positive: RETRO_DEVICE_ID_JOYPAD_RIGHT,
+            },
+        );
+
+        let value = remaps.input_state(
+            0,
+            RETRO_DEVICE_ANALOG,
+            RETRO_DEVICE_INDEX_ANALOG_LEFT,
+            RETRO_DEVICE_ID_ANALOG_X,
+            frontend,
+        );
+
+        assert_eq!(value, 0x7fff);
+    }
+
+    #[test]
+    fn save_and_reload() {
+        let remaps = RemapManager::new("retro_out_test/opt", "test_remap".to_string());
+        remaps.try_reload_remap(Some("rom"));
+
+        remaps.set_button(1, RETRO_DEVICE_ID_JOYPAD_B, RETRO_DEVICE_ID_JOYPAD_A);
+        remaps.set_turbo(1, RETRO_DEVICE_ID_JOYPAD_A, 6);
+        remaps.set_analog_to_digital(
+            1,
+            AnalogToDigital {
+                button: RETRO_DEVICE_ID_JOYPAD_LEFT,
+                index: RETRO_DEVICE_INDEX_ANALOG_LEFT,
+                axis: RETRO_DEVICE_ID_ANALOG_X,
+                positive: false,
+                threshold: 0x4000,
+            },
+        );
+
+        let expected = remaps.get_port(1);
+
+        remaps.save_remap(true).unwrap();
+        remaps.clear();
+        remaps.try_reload_remap(Some("rom"));
+
+        assert_eq!(remaps.get_port(1), expected);
+
+        remaps.try_reload_remap(Some("Super Mario Bros. 3"));
+        let game_file_path = remaps.game_file_path.read().unwrap().clone().unwrap();
+        assert_eq!(
+            game_file_path.file_name().unwrap(),
+            "Super Mario Bros. 3.rmp"
+        );
+    }
+}
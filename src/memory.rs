@@ -0,0 +1,424 @@
+use crate::{
+    erro_handle::{ErroHandle, RetroLogLevel},
+    retro_sys::{
+        retro_memory_map, RETRO_MEMDESC_BIGENDIAN, RETRO_MEMDESC_CONST, RETRO_MEMDESC_SAVE_RAM,
+        RETRO_MEMDESC_SYSTEM_RAM, RETRO_MEMDESC_VIDEO_RAM,
+    },
+    tools::ffi_tools::get_str_from_ptr,
+};
+use std::{ptr::null_mut, sync::RwLock};
+
+#[doc = "Descritor enviado pelo núcleo em RETRO_ENVIRONMENT_SET_MEMORY_MAPS."]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MemoryDescriptor {
+    pub flags: u64,
+    pub ptr: *mut u8,
+    pub offset: usize,
+    pub start: usize,
+    pub select: usize,
+    pub disconnect: usize,
+    pub len: usize,
+    pub addrspace: String,
+}
+
+// o ponteiro pertence ao núcleo e so e usado enquanto a rom estiver carregada,
+// os descritores sao removidos em unload_game
+unsafe impl Send for MemoryDescriptor {}
+unsafe impl Sync for MemoryDescriptor {}
+
+impl Default for MemoryDescriptor {
+    fn default() -> Self {
+        MemoryDescriptor {
+            flags: 0,
+            ptr: null_mut(),
+            offset: 0,
+            start: 0,
+            select: 0,
+            disconnect: 0,
+            len: 0,
+            addrspace: String::new(),
+        }
+    }
+}
+
+impl MemoryDescriptor {
+    pub fn is_read_only(&self) -> bool {
+        self.flags & RETRO_MEMDESC_CONST as u64 != 0
+    }
+
+    pub fn is_big_endian(&self) -> bool {
+        self.flags & RETRO_MEMDESC_BIGENDIAN as u64 != 0
+    }
+
+    pub fn is_system_ram(&self) -> bool {
+        self.flags & RETRO_MEMDESC_SYSTEM_RAM as u64 != 0
+    }
+
+    pub fn is_save_ram(&self) -> bool {
+        self.flags & RETRO_MEMDESC_SAVE_RAM as u64 != 0
+    }
+
+    pub fn is_video_ram(&self) -> bool {
+        self.flags & RETRO_MEMDESC_VIDEO_RAM as u64 != 0
+    }
+
+    fn matches(&self, address: usize) -> bool {
+        if self.select != 0 {
+            (address ^ self.start) & self.select == 0
+        } else {
+            address >= self.start && address - self.start < self.len
+        }
+    }
+
+    #[doc = "
+        Converte um endereço emulado para o índice dentro de `ptr`, seguindo a ordem
+        do libretro.h: subtrai `start`, remove os bits de `disconnect`, aplica `len` e
+        soma `offset`.
+    "]
+    pub fn get_physical_address(&self, address: usize) -> Option<usize> {
+        if !self.matches(address) {
+            return None;
+        }
+
+        let mut address = reduce(
+            address.wrapping_sub(self.start) & !self.select,
+            self.disconnect,
+        );
+
+        if self.len != 0 {
+            //regiões espelhadas, ex: a RAM de 2KB do NES repetida ate 0x1FFF
+            while address >= self.len {
+                address -= highest_bit(address);
+            }
+        }
+
+        Some(self.offset + address)
+    }
+}
+
+//remove os bits de `mask` de `address` juntando os bits restantes
+fn reduce(mut address: usize, mut mask: usize) -> usize {
+    while mask != 0 {
+        let tmp = (mask - 1) & !mask;
+        address = (address & tmp) | ((address >> 1) & !tmp);
+        mask = (mask & (mask - 1)) >> 1;
+    }
+
+    address
+}
+
+fn highest_bit(value: usize) -> usize {
+    1 << (usize::BITS - 1 - value.leading_zeros())
+}
+
+fn memory_error(message: String) -> ErroHandle {
+    ErroHandle {
+        level: RetroLogLevel::RETRO_LOG_ERROR,
+        message,
+    }
+}
+
+#[doc = "Memoria do núcleo somente para leitura, `id` e o mesmo usado em retro_get_memory_data."]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MemoryRegion<'a> {
+    pub id: u32,
+    pub data: &'a [u8],
+}
+
+#[doc = "
+    # Memoria emulada
+
+    Le e escreve na memoria do núcleo usando endereços do sistema emulado. Quando o núcleo
+    nao envia RETRO_ENVIRONMENT_SET_MEMORY_MAPS os endereços sao índices dentro de
+    RETRO_MEMORY_SYSTEM_RAM.
+"]
+#[derive(Debug, Default)]
+pub struct Memory {
+    pub descriptors: RwLock<Vec<MemoryDescriptor>>,
+    system_ram: RwLock<Option<MemoryDescriptor>>,
+}
+
+impl Memory {
+    pub(crate) unsafe fn set_memory_maps(&self, raw: *const retro_memory_map) {
+        let mut descriptors = self.descriptors.write().unwrap();
+        descriptors.clear();
+
+        if raw.is_null() || (*raw).descriptors.is_null() {
+            return;
+        }
+
+        let raw = *raw;
+
+        for index in 0..raw.num_descriptors as usize {
+            let raw_desc = *raw.descriptors.add(index);
+
+            descriptors.push(MemoryDescriptor {
+                flags: raw_desc.flags,
+                ptr: raw_desc.ptr as *mut u8,
+                offset: raw_desc.offset,
+                start: raw_desc.start,
+                select: raw_desc.select,
+                disconnect: raw_desc.disconnect,
+                len: raw_desc.len,
+                addrspace: if raw_desc.addrspace.is_null() {
+                    String::new()
+                } else {
+                    get_str_from_ptr(raw_desc.addrspace)
+                },
+            });
+        }
+    }
+
+    #[doc = "Usado quando o núcleo nao possui mapas de memoria, `ptr` vem de retro_get_memory_data."]
+    pub(crate) fn set_system_ram(&self, ptr: *mut u8, len: usize) {
+        *self.system_ram.write().unwrap() = if ptr.is_null() || len == 0 {
+            None
+        } else {
+            Some(MemoryDescriptor {
+                flags: RETRO_MEMDESC_SYSTEM_RAM as u64,
+                ptr,
+                offset: 0,
+                start: 0,
+                select: 0,
+                disconnect: 0,
+                len,
+                addrspace: String::new(),
+            })
+        };
+    }
+
+    pub fn clear(&self) {
+        self.descriptors.write().unwrap().clear();
+        *self.system_ram.write().unwrap() = None;
+    }
+
+    pub fn has_memory_maps(&self) -> bool {
+        !self.descriptors.read().unwrap().is_empty()
+    }
+
+    fn resolve(&self, address: usize) -> Result<(*mut u8, bool, bool), ErroHandle> {
+        self.resolve_in(address, false)
+    }
+
+    //com `system_ram_only` o endereço e um índice dentro de RETRO_MEMORY_SYSTEM_RAM
+    fn resolve_in(
+        &self,
+        address: usize,
+        system_ram_only: bool,
+    ) -> Result<(*mut u8, bool, bool), ErroHandle> {
+        let descriptors = self.descriptors.read().unwrap();
+        let system_ram = self.system_ram.read().unwrap();
+
+        let candidates = if system_ram_only || descriptors.is_empty() {
+            system_ram.as_slice()
+        } else {
+            descriptors.as_slice()
+        };
+
+        for desc in candidates {
+            //sem len nao tem como saber o tamanho da região
+            if desc.ptr.is_null() || desc.len == 0 {
+                continue;
+            }
+
+            if let Some(physical) = desc.get_physical_address(address) {
+                let ptr = unsafe { desc.ptr.add(physical) };
+                return Ok((ptr, desc.is_big_endian(), desc.is_read_only()));
+            }
+        }
+
+        Err(memory_error(format!(
+            "O endereço 0x{:X} nao esta mapeado",
+            address
+        )))
+    }
+
+    pub fn read_bytes(&self, address: usize, buffer: &mut [u8]) -> Result<(), ErroHandle> {
+        for (index, byte) in buffer.iter_mut().enumerate() {
+            let (ptr, _, _) = self.resolve(address + index)?;
+            *byte = unsafe { *ptr };
+        }
+
+        Ok(())
+    }
+
+    pub fn write_bytes(&self, address: usize, data: &[u8]) -> Result<(), ErroHandle> {
+        self.write_bytes_in(address, data, false)
+    }
+
+    #[doc = "
+        Escreve em RETRO_MEMORY_SYSTEM_RAM mesmo quando o núcleo possui mapas de memoria,
+        `offset` e o mesmo índice usado por [crate::ram_search::RamSearch].
+    "]
+    pub fn write_system_ram(&self, offset: usize, data: &[u8]) -> Result<(), ErroHandle> {
+        self.write_bytes_in(offset, data, true)
+    }
+
+    fn write_bytes_in(
+        &self,
+        address: usize,
+        data: &[u8],
+        system_ram_only: bool,
+    ) -> Result<(), ErroHandle> {
+        //resolve tudo antes para nao deixar uma escrita pela metade
+        let mut ptrs = Vec::with_capacity(data.len());
+
+        for index in 0..data.len() {
+            let (ptr, _, read_only) = self.resolve_in(address + index, system_ram_only)?;
+
+            if read_only {
+                return Err(memory_error(format!(
+                    "O endereço 0x{:X} e somente leitura",
+                    address + index
+                )));
+            }
+
+            ptrs.push(ptr);
+        }
+
+        for (ptr, byte) in ptrs.into_iter().zip(data) {
+            unsafe { *ptr = *byte };
+        }
+
+        Ok(())
+    }
+
+    fn is_big_endian(&self, address: usize) -> Result<bool, ErroHandle> {
+        Ok(self.resolve(address)?.1)
+    }
+
+    pub fn read_u8(&self, address: usize) -> Result<u8, ErroHandle> {
+        let mut buffer = [0; 1];
+        self.read_bytes(address, &mut buffer)?;
+
+        Ok(buffer[0])
+    }
+
+    pub fn read_u16(&self, address: usize) -> Result<u16, ErroHandle> {
+        let mut buffer = [0; 2];
+        self.read_bytes(address, &mut buffer)?;
+
+        if self.is_big_endian(address)? {
+            Ok(u16::from_be_bytes(buffer))
+        } else {
+            Ok(u16::from_le_bytes(buffer))
+        }
+    }
+
+    pub fn read_u32(&self, address: usize) -> Result<u32, ErroHandle> {
+        let mut buffer = [0; 4];
+        self.read_bytes(address, &mut buffer)?;
+
+        if self.is_big_endian(address)? {
+            Ok(u32::from_be_bytes(buffer))
+        } else {
+            Ok(u32::from_le_bytes(buffer))
+        }
+    }
+
+    pub fn write_u8(&self, address: usize, value: u8) -> Result<(), ErroHandle> {
+        self.write_bytes(address, &[value])
+    }
+
+    pub fn write_u16(&self, address: usize, value: u16) -> Result<(), ErroHandle> {
+        if self.is_big_endian(address)? {
+            self.write_bytes(address, &value.to_be_bytes())
+        } else {
+            self.write_bytes(address, &value.to_le_bytes())
+        }
+    }
+
+    pub fn write_u32(&self, address: usize, value: u32) -> Result<(), ErroHandle> {
+        if self.is_big_endian(address)? {
+            self.write_bytes(address, &value.to_be_bytes())
+        } else {
+            self.write_bytes(address, &value.to_le_bytes())
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_memory {
+    use crate::{
+        memory::Memory,
+        retro_sys::{
+            retro_memory_descriptor, retro_memory_map, RETRO_MEMDESC_BIGENDIAN, RETRO_MEMDESC_CONST,
+        },
+    };
+    use std::{os::raw::c_void, ptr::null};
+
+    fn get_descriptor(
+        data: &mut [u8],
+        flags: u32,
+        start: usize,
+        select: usize,
+        len: usize,
+    ) -> retro_memory_descriptor {
+        retro_memory_descriptor {
+            flags: flags as u64,
+            ptr: data.as_mut_ptr() as *mut c_void,
+            offset: 0,
+            start,
+            select,
+            disconnect: 0,
+            len,
+            addrspace: null(),
+        }
+    }
+
+    #[test]
+    fn memory_maps() {
+        let mut ram = vec![0u8; 0x800];
+        let mut rom = vec![0u8; 0x10];
+        rom[0] = 0x12;
+        rom[1] = 0x34;
+
+        let descriptors = [
+            //RAM de 2KB espelhada ate 0x1FFF
+            get_descriptor(&mut ram, 0, 0, 0xE000, 0x800),
+            get_descriptor(
+                &mut rom,
+                RETRO_MEMDESC_CONST | RETRO_MEMDESC_BIGENDIAN,
+                0x8000,
+                0,
+                0x10,
+            ),
+        ];
+
+        let memory = Memory::default();
+        unsafe {
+            memory.set_memory_maps(&retro_memory_map {
+                descriptors: descriptors.as_ptr(),
+                num_descriptors: descriptors.len() as u32,
+            })
+        };
+
+        memory.write_u16(0x10, 0xBEEF).unwrap();
+        assert_eq!(ram[0x10], 0xEF);
+        assert_eq!(memory.read_u16(0x810).unwrap(), 0xBEEF);
+        assert_eq!(memory.read_u8(0x1811).unwrap(), 0xBE);
+
+        assert_eq!(memory.read_u16(0x8000).unwrap(), 0x1234);
+        assert!(memory.write_u8(0x8000, 0).is_err());
+        assert!(memory.read_u32(0x800E).is_err());
+        assert!(memory.read_u8(0x4000).is_err());
+    }
+
+    #[test]
+    fn system_ram_fallback() {
+        let mut ram = vec![0u8; 4];
+        let memory = Memory::default();
+
+        assert!(memory.read_u8(0).is_err());
+
+        memory.set_system_ram(ram.as_mut_ptr(), ram.len());
+        memory.write_u32(0, 0x01020304).unwrap();
+
+        assert_eq!(ram, [4, 3, 2, 1]);
+        assert_eq!(memory.read_u32(0).unwrap(), 0x01020304);
+        assert!(memory.read_u8(4).is_err());
+
+        memory.clear();
+        assert!(memory.read_u8(0).is_err());
+    }
+}
@@ -0,0 +1,153 @@
+use crate::{
+    constants::MAX_CORE_MESSAGES,
+    retro_sys::{
+        retro_log_level, retro_message, retro_message_ext, retro_message_target, retro_message_type,
+    },
+    tools::ffi_tools::get_str_from_ptr,
+};
+use std::{collections::VecDeque, sync::Mutex};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MessageDuration {
+    #[doc = "Enviado por RETRO_ENVIRONMENT_SET_MESSAGE."]
+    Frames(u32),
+    #[doc = "Enviado por RETRO_ENVIRONMENT_SET_MESSAGE_EXT."]
+    Milliseconds(u32),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CoreMessage {
+    pub msg: String,
+    pub duration: MessageDuration,
+    #[doc = "Mensagens com prioridade maior devem substituir as de menor prioridade."]
+    pub priority: u32,
+    pub level: retro_log_level,
+    pub target: retro_message_target,
+    pub msg_type: retro_message_type,
+    #[doc = "Progresso de 0 a 100, -1 quando indefinido, so e usado em RETRO_MESSAGE_TYPE_PROGRESS."]
+    pub progress: i8,
+}
+
+impl CoreMessage {
+    pub fn from_raw(raw: &retro_message) -> Self {
+        CoreMessage {
+            msg: get_str_from_ptr(raw.msg),
+            duration: MessageDuration::Frames(raw.frames),
+            priority: 0,
+            level: retro_log_level::RETRO_LOG_INFO,
+            target: retro_message_target::RETRO_MESSAGE_TARGET_ALL,
+            msg_type: retro_message_type::RETRO_MESSAGE_TYPE_NOTIFICATION,
+            progress: -1,
+        }
+    }
+
+    pub fn from_raw_ext(raw: &retro_message_ext) -> Self {
+        CoreMessage {
+            msg: get_str_from_ptr(raw.msg),
+            duration: MessageDuration::Milliseconds(raw.duration),
+            priority: raw.priority,
+            level: raw.level,
+            target: raw.target,
+            msg_type: raw.type_,
+            progress: raw.progress,
+        }
+    }
+
+    #[doc = "Verdadeiro se a mensagem deve ser desenhada na tela."]
+    pub fn is_osd(&self) -> bool {
+        self.target != retro_message_target::RETRO_MESSAGE_TARGET_LOG
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct MessageQueue {
+    messages: Mutex<VecDeque<CoreMessage>>,
+}
+
+impl MessageQueue {
+    #[doc = "Se o frontend nao esvaziar a fila as mensagens mais antigas sao descartadas."]
+    pub fn push(&self, message: CoreMessage) {
+        let mut messages = self.messages.lock().unwrap();
+
+        if messages.len() >= MAX_CORE_MESSAGES {
+            messages.pop_front();
+        }
+
+        messages.push_back(message);
+    }
+
+    #[doc = "Remove e retorna todas as mensagens na ordem em que chegaram."]
+    pub fn drain(&self) -> Vec<CoreMessage> {
+        self.messages.lock().unwrap().drain(..).collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.messages.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[cfg(test)]
+mod test_message {
+    use crate::{
+        constants::MAX_CORE_MESSAGES,
+        message::{CoreMessage, MessageDuration, MessageQueue},
+        retro_sys::{
+            retro_log_level, retro_message, retro_message_ext, retro_message_target,
+            retro_message_type,
+        },
+        tools::ffi_tools::make_c_string,
+    };
+
+    #[test]
+    fn from_raw() {
+        let msg = make_c_string("Disk 2 inserted").unwrap();
+
+        let message = CoreMessage::from_raw(&retro_message {
+            msg: msg.as_ptr(),
+            frames: 180,
+        });
+
+        assert_eq!(message.msg, "Disk 2 inserted");
+        assert_eq!(message.duration, MessageDuration::Frames(180));
+        assert!(message.is_osd());
+
+        let message = CoreMessage::from_raw_ext(&retro_message_ext {
+            msg: msg.as_ptr(),
+            duration: 3000,
+            priority: 2,
+            level: retro_log_level::RETRO_LOG_WARN,
+            target: retro_message_target::RETRO_MESSAGE_TARGET_LOG,
+            type_: retro_message_type::RETRO_MESSAGE_TYPE_PROGRESS,
+            progress: 50,
+        });
+
+        assert_eq!(message.duration, MessageDuration::Milliseconds(3000));
+        assert_eq!(message.priority, 2);
+        assert_eq!(message.progress, 50);
+        assert!(!message.is_osd());
+    }
+
+    #[test]
+    fn queue() {
+        let queue = MessageQueue::default();
+        let msg = make_c_string("Memory card saved").unwrap();
+
+        for frames in 0..MAX_CORE_MESSAGES + 2 {
+            queue.push(CoreMessage::from_raw(&retro_message {
+                msg: msg.as_ptr(),
+                frames: frames as u32,
+            }));
+        }
+
+        assert_eq!(queue.len(), MAX_CORE_MESSAGES);
+
+        let messages = queue.drain();
+
+        assert_eq!(messages[0].duration, MessageDuration::Frames(2));
+        assert!(queue.is_empty());
+    }
+}
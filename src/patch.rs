@@ -0,0 +1,530 @@
+use crate::erro_handle::{ErroHandle, RetroLogLevel};
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PatchKind {
+    Ips,
+    Bps,
+    Ups,
+}
+
+impl PatchKind {
+    pub fn from_path(path: &Path) -> Option<PatchKind> {
+        let extension = path.extension()?.to_string_lossy().to_lowercase();
+
+        match extension.as_str() {
+            "ips" => Some(PatchKind::Ips),
+            "bps" => Some(PatchKind::Bps),
+            "ups" => Some(PatchKind::Ups),
+            _ => None,
+        }
+    }
+
+    fn get_extension(&self) -> &str {
+        match self {
+            PatchKind::Ips => "ips",
+            PatchKind::Bps => "bps",
+            PatchKind::Ups => "ups",
+        }
+    }
+}
+
+#[doc = "Procura `<dir>/<name>.ips`, `.bps` ou `.ups` nessa ordem."]
+pub fn find_patch(dir: &Path, name: &str) -> Option<PathBuf> {
+    [PatchKind::Ips, PatchKind::Bps, PatchKind::Ups]
+        .iter()
+        .map(|kind| dir.join(name.to_string() + "." + kind.get_extension()))
+        .find(|path| path.is_file())
+}
+
+fn patch_error(message: &str) -> ErroHandle {
+    ErroHandle {
+        level: RetroLogLevel::RETRO_LOG_ERROR,
+        message: "Patch invalido: ".to_string() + message,
+    }
+}
+
+// o tamanho do resultado vem do cabeçalho do patch, sem um limite um patch corrompido
+// poderia pedir gigabytes. Os 64MB cobrem as expansões de rom comuns mesmo com patches pequenos
+const MIN_TARGET_LIMIT: usize = 64 * 1024 * 1024;
+
+fn check_target_size(target_size: usize, source: &[u8], patch: &[u8]) -> Result<(), ErroHandle> {
+    let limit = source
+        .len()
+        .saturating_add(patch.len())
+        .saturating_mul(4)
+        .max(MIN_TARGET_LIMIT);
+
+    if target_size > limit {
+        return Err(patch_error("o tamanho do resultado e grande demais"));
+    }
+
+    Ok(())
+}
+
+fn checked_end(start: usize, size: usize) -> Result<usize, ErroHandle> {
+    start
+        .checked_add(size)
+        .ok_or_else(|| patch_error("tamanho fora do limite"))
+}
+
+pub fn apply_patch(kind: PatchKind, source: &[u8], patch: &[u8]) -> Result<Vec<u8>, ErroHandle> {
+    match kind {
+        PatchKind::Ips => apply_ips(source, patch),
+        PatchKind::Bps => apply_bps(source, patch),
+        PatchKind::Ups => apply_ups(source, patch),
+    }
+}
+
+struct PatchReader<'a> {
+    patch: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> PatchReader<'a> {
+    fn read_u8(&mut self) -> Result<u8, ErroHandle> {
+        let byte = *self
+            .patch
+            .get(self.pos)
+            .ok_or_else(|| patch_error("fim inesperado do arquivo"))?;
+        self.pos += 1;
+
+        Ok(byte)
+    }
+
+    fn read_slice(&mut self, size: usize) -> Result<&'a [u8], ErroHandle> {
+        let slice = self
+            .patch
+            .get(self.pos..checked_end(self.pos, size)?)
+            .ok_or_else(|| patch_error("fim inesperado do arquivo"))?;
+        self.pos += size;
+
+        Ok(slice)
+    }
+
+    fn read_be(&mut self, size: usize) -> Result<usize, ErroHandle> {
+        Ok(self
+            .read_slice(size)?
+            .iter()
+            .fold(0, |value, byte| (value << 8) | *byte as usize))
+    }
+
+    //inteiro de tamanho variável usado pelo BPS e UPS
+    fn read_number(&mut self) -> Result<usize, ErroHandle> {
+        let mut value = 0usize;
+        let mut shift = 1usize;
+
+        loop {
+            let byte = self.read_u8()?;
+            value = value
+                .checked_add((byte & 0x7f) as usize * shift)
+                .ok_or_else(|| patch_error("numero muito grande"))?;
+
+            if byte & 0x80 != 0 {
+                return Ok(value);
+            }
+
+            shift = shift
+                .checked_shl(7)
+                .ok_or_else(|| patch_error("numero muito grande"))?;
+            value = value
+                .checked_add(shift)
+                .ok_or_else(|| patch_error("numero muito grande"))?;
+        }
+    }
+}
+
+fn apply_ips(source: &[u8], patch: &[u8]) -> Result<Vec<u8>, ErroHandle> {
+    let mut reader = PatchReader { patch, pos: 0 };
+
+    if reader.read_slice(5)? != b"PATCH" {
+        return Err(patch_error("cabeçalho IPS nao encontrado"));
+    }
+
+    let mut target = source.to_vec();
+
+    loop {
+        let offset_bytes = reader.read_slice(3)?;
+
+        if offset_bytes == b"EOF" {
+            break;
+        }
+
+        let offset = offset_bytes
+            .iter()
+            .fold(0, |value, byte| (value << 8) | *byte as usize);
+        let size = reader.read_be(2)?;
+
+        let (size, data) = if size == 0 {
+            //registro RLE: o mesmo byte repetido
+            let size = reader.read_be(2)?;
+            (size, vec![reader.read_u8()?; size])
+        } else {
+            (size, reader.read_slice(size)?.to_vec())
+        };
+
+        let end = checked_end(offset, size)?;
+
+        if target.len() < end {
+            target.resize(end, 0);
+        }
+
+        target[offset..end].copy_from_slice(&data);
+    }
+
+    //extensão do formato que corta a rom depois do EOF
+    if reader.pos + 3 <= patch.len() {
+        let truncate = reader.read_be(3)?;
+        target.truncate(truncate);
+    }
+
+    Ok(target)
+}
+
+struct PatchFooter {
+    source_crc: u32,
+    target_crc: u32,
+}
+
+fn read_footer(patch: &[u8]) -> Result<PatchFooter, ErroHandle> {
+    if patch.len() < 16 {
+        return Err(patch_error("arquivo muito pequeno"));
+    }
+
+    let read_crc = |pos: usize| u32::from_le_bytes(patch[pos..pos + 4].try_into().unwrap());
+    let footer = patch.len() - 12;
+
+    if crc32fast::hash(&patch[..patch.len() - 4]) != read_crc(footer + 8) {
+        return Err(patch_error("CRC32 do patch nao confere"));
+    }
+
+    Ok(PatchFooter {
+        source_crc: read_crc(footer),
+        target_crc: read_crc(footer + 4),
+    })
+}
+
+fn apply_bps(source: &[u8], patch: &[u8]) -> Result<Vec<u8>, ErroHandle> {
+    let footer = read_footer(patch)?;
+    let mut reader = PatchReader { patch, pos: 0 };
+
+    if reader.read_slice(4)? != b"BPS1" {
+        return Err(patch_error("cabeçalho BPS nao encontrado"));
+    }
+
+    if crc32fast::hash(source) != footer.source_crc {
+        return Err(patch_error("o patch BPS foi feito para outra rom"));
+    }
+
+    let source_size = reader.read_number()?;
+    let target_size = reader.read_number()?;
+    let metadata_size = reader.read_number()?;
+    reader.read_slice(metadata_size)?;
+
+    if source_size != source.len() {
+        return Err(patch_error(
+            "tamanho da rom diferente do esperado pelo patch BPS",
+        ));
+    }
+
+    check_target_size(target_size, source, patch)?;
+
+    let mut target = Vec::with_capacity(target_size);
+    let mut source_offset = 0isize;
+    let mut target_offset = 0isize;
+    let actions_end = patch.len() - 12;
+
+    let read_offset = |reader: &mut PatchReader, offset: &mut isize| -> Result<(), ErroHandle> {
+        let data = reader.read_number()?;
+        let delta = (data >> 1) as isize;
+
+        *offset = if data & 1 != 0 {
+            offset.checked_sub(delta)
+        } else {
+            offset.checked_add(delta)
+        }
+        .ok_or_else(|| patch_error("leitura fora da rom"))?;
+
+        Ok(())
+    };
+
+    while reader.pos < actions_end {
+        let data = reader.read_number()?;
+        let length = (data >> 2) + 1;
+
+        if checked_end(target.len(), length)? > target_size {
+            return Err(patch_error(
+                "o resultado do patch BPS e maior que o esperado",
+            ));
+        }
+
+        match data & 3 {
+            //SourceRead
+            0 => {
+                let start = target.len();
+                let bytes = source
+                    .get(start..checked_end(start, length)?)
+                    .ok_or_else(|| patch_error("leitura fora da rom"))?;
+                target.extend_from_slice(bytes);
+            }
+            //TargetRead
+            1 => target.extend_from_slice(reader.read_slice(length)?),
+            //SourceCopy
+            2 => {
+                read_offset(&mut reader, &mut source_offset)?;
+
+                let start = usize::try_from(source_offset)
+                    .map_err(|_| patch_error("leitura fora da rom"))?;
+                let bytes = source
+                    .get(start..checked_end(start, length)?)
+                    .ok_or_else(|| patch_error("leitura fora da rom"))?;
+
+                target.extend_from_slice(bytes);
+                source_offset = isize::try_from(length)
+                    .ok()
+                    .and_then(|length| source_offset.checked_add(length))
+                    .ok_or_else(|| patch_error("leitura fora da rom"))?;
+            }
+            //TargetCopy, pode repetir bytes que acabaram de ser escritos
+            _ => {
+                read_offset(&mut reader, &mut target_offset)?;
+
+                for _ in 0..length {
+                    let byte = usize::try_from(target_offset)
+                        .ok()
+                        .and_then(|pos| target.get(pos).copied())
+                        .ok_or_else(|| patch_error("leitura fora do resultado"))?;
+
+                    target.push(byte);
+                    target_offset += 1;
+                }
+            }
+        }
+    }
+
+    if target.len() != target_size || crc32fast::hash(&target) != footer.target_crc {
+        return Err(patch_error("o resultado do patch BPS nao confere"));
+    }
+
+    Ok(target)
+}
+
+fn apply_ups(source: &[u8], patch: &[u8]) -> Result<Vec<u8>, ErroHandle> {
+    let footer = read_footer(patch)?;
+    let mut reader = PatchReader { patch, pos: 0 };
+
+    if reader.read_slice(4)? != b"UPS1" {
+        return Err(patch_error("cabeçalho UPS nao encontrado"));
+    }
+
+    if crc32fast::hash(source) != footer.source_crc {
+        return Err(patch_error("o patch UPS foi feito para outra rom"));
+    }
+
+    let source_size = reader.read_number()?;
+    let target_size = reader.read_number()?;
+
+    if source_size != source.len() {
+        return Err(patch_error(
+            "tamanho da rom diferente do esperado pelo patch UPS",
+        ));
+    }
+
+    check_target_size(target_size, source, patch)?;
+
+    let mut target = source.to_vec();
+    target.resize(target_size, 0);
+
+    let actions_end = patch.len() - 12;
+    let mut pos = 0usize;
+
+    while reader.pos < actions_end {
+        pos = checked_end(pos, reader.read_number()?)?;
+
+        loop {
+            let byte = reader.read_u8()?;
+            pos = checked_end(pos, 1)?;
+
+            if byte == 0 {
+                break;
+            }
+
+            if let Some(target_byte) = target.get_mut(pos - 1) {
+                *target_byte ^= byte;
+            }
+        }
+    }
+
+    if crc32fast::hash(&target) != footer.target_crc {
+        return Err(patch_error("o resultado do patch UPS nao confere"));
+    }
+
+    Ok(target)
+}
+
+#[cfg(test)]
+mod test_patch {
+    use crate::patch::{apply_patch, find_patch, PatchKind};
+    use std::{fs, path::Path};
+
+    fn encode_number(mut value: usize, out: &mut Vec<u8>) {
+        loop {
+            let byte = (value & 0x7f) as u8;
+            value >>= 7;
+
+            if value == 0 {
+                out.push(byte | 0x80);
+                return;
+            }
+
+            out.push(byte);
+            value -= 1;
+        }
+    }
+
+    fn finish(mut patch: Vec<u8>, source: &[u8], target: &[u8]) -> Vec<u8> {
+        patch.extend_from_slice(&crc32fast::hash(source).to_le_bytes());
+        patch.extend_from_slice(&crc32fast::hash(target).to_le_bytes());
+        let crc = crc32fast::hash(&patch);
+        patch.extend_from_slice(&crc.to_le_bytes());
+
+        patch
+    }
+
+    #[test]
+    fn ips() {
+        let source = [0u8; 8];
+
+        let mut patch = b"PATCH".to_vec();
+        patch.extend_from_slice(&[0, 0, 2, 0, 2, 0xAA, 0xBB]);
+        //RLE com 3 bytes 0x11 depois do fim da rom
+        patch.extend_from_slice(&[0, 0, 8, 0, 0, 0, 3, 0x11]);
+        patch.extend_from_slice(b"EOF");
+
+        let target = apply_patch(PatchKind::Ips, &source, &patch).unwrap();
+        assert_eq!(target, [0, 0, 0xAA, 0xBB, 0, 0, 0, 0, 0x11, 0x11, 0x11]);
+
+        patch.extend_from_slice(&[0, 0, 4]);
+        let target = apply_patch(PatchKind::Ips, &source, &patch).unwrap();
+        assert_eq!(target, [0, 0, 0xAA, 0xBB]);
+
+        assert!(apply_patch(PatchKind::Ips, &source, b"PATCH").is_err());
+    }
+
+    #[test]
+    fn bps() {
+        let source = b"abcdef".to_vec();
+        let target = b"abcXYXYXdef".to_vec();
+
+        let mut patch = b"BPS1".to_vec();
+        encode_number(source.len(), &mut patch);
+        encode_number(target.len(), &mut patch);
+        encode_number(0, &mut patch);
+        //SourceRead de 3 bytes
+        encode_number((3 - 1) << 2, &mut patch);
+        //TargetRead de "XY"
+        encode_number(((2 - 1) << 2) | 1, &mut patch);
+        patch.extend_from_slice(b"XY");
+        //TargetCopy de 3 bytes a partir de "X"
+        encode_number(((3 - 1) << 2) | 3, &mut patch);
+        encode_number(3 << 1, &mut patch);
+        //SourceCopy de "def"
+        encode_number(((3 - 1) << 2) | 2, &mut patch);
+        encode_number(3 << 1, &mut patch);
+
+        let patch = finish(patch, &source, &target);
+
+        assert_eq!(
+            apply_patch(PatchKind::Bps, &source, &patch).unwrap(),
+            target
+        );
+        assert!(apply_patch(PatchKind::Bps, b"outra", &patch).is_err());
+
+        let mut corrupted = patch.clone();
+        corrupted[6] ^= 1;
+        assert!(apply_patch(PatchKind::Bps, &source, &corrupted).is_err());
+    }
+
+    #[test]
+    fn oversized() {
+        let source = b"abcdef".to_vec();
+
+        //o cabeçalho pede um resultado de 1TB
+        let mut patch = b"BPS1".to_vec();
+        encode_number(source.len(), &mut patch);
+        encode_number(1 << 40, &mut patch);
+        encode_number(0, &mut patch);
+        let patch = finish(patch, &source, &source);
+        assert!(apply_patch(PatchKind::Bps, &source, &patch).is_err());
+
+        let mut patch = b"UPS1".to_vec();
+        encode_number(source.len(), &mut patch);
+        encode_number(1 << 40, &mut patch);
+        let patch = finish(patch, &source, &source);
+        assert!(apply_patch(PatchKind::Ups, &source, &patch).is_err());
+
+        //um numero com mais bits do que cabem em um usize
+        let mut patch = b"BPS1".to_vec();
+        patch.extend_from_slice(&[0x7f; 12]);
+        patch.push(0xff);
+        let patch = finish(patch, &source, &source);
+        assert!(apply_patch(PatchKind::Bps, &source, &patch).is_err());
+
+        //a ação pede mais bytes do que o tamanho informado no cabeçalho
+        let mut patch = b"BPS1".to_vec();
+        encode_number(source.len(), &mut patch);
+        encode_number(2, &mut patch);
+        encode_number(0, &mut patch);
+        encode_number(((usize::MAX >> 2) - 1) << 2, &mut patch);
+        let patch = finish(patch, &source, b"ab");
+        assert!(apply_patch(PatchKind::Bps, &source, &patch).is_err());
+
+        //SourceCopy com um deslocamento negativo enorme
+        let mut patch = b"BPS1".to_vec();
+        encode_number(source.len(), &mut patch);
+        encode_number(2, &mut patch);
+        encode_number(0, &mut patch);
+        encode_number(((2 - 1) << 2) | 2, &mut patch);
+        encode_number(usize::MAX, &mut patch);
+        let patch = finish(patch, &source, b"ab");
+        assert!(apply_patch(PatchKind::Bps, &source, &patch).is_err());
+    }
+
+    #[test]
+    fn ups() {
+        let source = b"abcdef".to_vec();
+        let mut target = b"abXdef".to_vec();
+        target.extend_from_slice(b"Z");
+
+        let mut patch = b"UPS1".to_vec();
+        encode_number(source.len(), &mut patch);
+        encode_number(target.len(), &mut patch);
+        encode_number(2, &mut patch);
+        patch.extend_from_slice(&[b'c' ^ b'X', 0]);
+        //o terminador tambem avança uma posição, "Z" fica 2 bytes depois
+        encode_number(2, &mut patch);
+        patch.extend_from_slice(&[b'Z', 0]);
+
+        let patch = finish(patch, &source, &target);
+
+        assert_eq!(
+            apply_patch(PatchKind::Ups, &source, &patch).unwrap(),
+            target
+        );
+        assert!(apply_patch(PatchKind::Ups, b"abcdeg", &patch).is_err());
+    }
+
+    #[test]
+    fn find_patch_file() {
+        let dir = Path::new("retro_out_test/patch");
+        fs::create_dir_all(dir).unwrap();
+        fs::write(dir.join("game.bps"), b"BPS1").unwrap();
+
+        assert_eq!(find_patch(dir, "game"), Some(dir.join("game.bps")));
+        assert_eq!(find_patch(dir, "outro"), None);
+        assert_eq!(
+            PatchKind::from_path(Path::new("hack.UPS")),
+            Some(PatchKind::Ups)
+        );
+    }
+}
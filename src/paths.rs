@@ -10,13 +10,16 @@ pub struct Paths {
     pub save: String,
     pub opt: String,
     pub assets: String,
+    pub cheats: String,
 }
 
 impl PartialEq for Paths {
     fn eq(&self, other: &Self) -> bool {
         other.assets == self.assets &&
         other.system == self.system &&
-        other.system == self.system
+        other.save == self.save &&
+        other.opt == self.opt &&
+        other.cheats == self.cheats
     }
 }
 
@@ -55,11 +58,34 @@ impl Paths {
             });
         }
 
+        let cheats = Path::new(&save).join("cheats").display().to_string();
+        create_cheats_dir(&cheats)?;
+
         Ok(Paths {
             system,
             opt,
             save,
             assets,
+            cheats,
         })
     }
+
+    #[doc = "Troca a pasta dos arquivos .cht, por padrão ela fica dentro da pasta save."]
+    pub fn with_cheats(mut self, cheats: String) -> Result<Self, ErroHandle> {
+        create_cheats_dir(&cheats)?;
+        self.cheats = cheats;
+
+        Ok(self)
+    }
+}
+
+fn create_cheats_dir(cheats: &str) -> Result<(), ErroHandle> {
+    if Path::new(cheats).exists().not() && fs::create_dir_all(cheats).is_err() {
+        return Err(ErroHandle {
+            level: RETRO_LOG_ERROR,
+            message: "Não foi possível criar a pasta cheats".to_owned(),
+        });
+    }
+
+    Ok(())
 }
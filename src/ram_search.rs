@@ -0,0 +1,328 @@
+use crate::managers::cheat_manager::Cheat;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SearchSize {
+    Bits8,
+    Bits16,
+    Bits32,
+}
+
+impl SearchSize {
+    pub fn get_bytes(&self) -> usize {
+        match self {
+            SearchSize::Bits8 => 1,
+            SearchSize::Bits16 => 2,
+            SearchSize::Bits32 => 4,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SearchOptions {
+    pub size: SearchSize,
+    pub signed: bool,
+    pub big_endian: bool,
+}
+
+impl Default for SearchOptions {
+    fn default() -> Self {
+        SearchOptions {
+            size: SearchSize::Bits8,
+            signed: false,
+            big_endian: false,
+        }
+    }
+}
+
+impl SearchOptions {
+    #[doc = "Lê o valor em `address` seguindo o tamanho, sinal e endianness escolhidos."]
+    pub fn read(&self, memory: &[u8], address: usize) -> Option<i64> {
+        let bytes = memory.get(address..address + self.size.get_bytes())?;
+
+        let value = match (self.size, self.big_endian) {
+            (SearchSize::Bits8, _) => bytes[0] as u32,
+            (SearchSize::Bits16, false) => u16::from_le_bytes([bytes[0], bytes[1]]) as u32,
+            (SearchSize::Bits16, true) => u16::from_be_bytes([bytes[0], bytes[1]]) as u32,
+            (SearchSize::Bits32, false) => {
+                u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+            }
+            (SearchSize::Bits32, true) => {
+                u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+            }
+        };
+
+        if !self.signed {
+            return Some(value as i64);
+        }
+
+        Some(match self.size {
+            SearchSize::Bits8 => value as u8 as i8 as i64,
+            SearchSize::Bits16 => value as u16 as i16 as i64,
+            SearchSize::Bits32 => value as i32 as i64,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SearchCompare {
+    Equal,
+    NotEqual,
+    Greater,
+    Less,
+    GreaterOrEqual,
+    LessOrEqual,
+}
+
+impl SearchCompare {
+    fn compare(&self, value: i64, target: i64) -> bool {
+        match self {
+            SearchCompare::Equal => value == target,
+            SearchCompare::NotEqual => value != target,
+            SearchCompare::Greater => value > target,
+            SearchCompare::Less => value < target,
+            SearchCompare::GreaterOrEqual => value >= target,
+            SearchCompare::LessOrEqual => value <= target,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SearchTarget {
+    #[doc = "Compara com o valor da ultima busca."]
+    Previous,
+    Value(i64),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SearchCandidate {
+    pub address: usize,
+    pub value: i64,
+    pub previous: i64,
+}
+
+#[doc = "
+    # Busca na RAM
+
+    Usada para encontrar o endereço de um valor do jogo (vidas, dinheiro...) e criar um cheat.
+    Recebe os bytes de RETRO_MEMORY_SYSTEM_RAM, veja [crate::core::CoreWrapper::get_memory_data].
+"]
+#[derive(Debug, Clone)]
+pub struct RamSearch {
+    pub options: SearchOptions,
+    snapshot: Vec<u8>,
+    candidates: Vec<usize>,
+}
+
+impl RamSearch {
+    pub fn new(memory: &[u8], options: SearchOptions) -> RamSearch {
+        let mut search = RamSearch {
+            options,
+            snapshot: Vec::new(),
+            candidates: Vec::new(),
+        };
+
+        search.reset(memory);
+        search
+    }
+
+    #[doc = "Volta a considerar todos os endereços alinhados ao tamanho escolhido."]
+    pub fn reset(&mut self, memory: &[u8]) {
+        let step = self.options.size.get_bytes();
+
+        self.snapshot = memory.to_vec();
+        self.candidates = (0..memory.len())
+            .step_by(step)
+            .filter(|address| address + step <= memory.len())
+            .collect();
+    }
+
+    #[doc = "Remove os candidatos que nao passam na comparação e retorna quantos sobraram."]
+    pub fn search(&mut self, memory: &[u8], compare: SearchCompare, target: SearchTarget) -> usize {
+        let options = self.options;
+        let snapshot = &self.snapshot;
+
+        self.candidates.retain(|address| {
+            let value = match options.read(memory, *address) {
+                Some(value) => value,
+                None => return false,
+            };
+
+            let target = match target {
+                SearchTarget::Value(value) => value,
+                SearchTarget::Previous => match options.read(snapshot, *address) {
+                    Some(previous) => previous,
+                    None => return false,
+                },
+            };
+
+            compare.compare(value, target)
+        });
+
+        self.snapshot = memory.to_vec();
+        self.candidates.len()
+    }
+
+    pub fn len(&self) -> usize {
+        self.candidates.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.candidates.is_empty()
+    }
+
+    #[doc = "Retorna ate `max` candidatos com o valor atual e o valor da ultima busca."]
+    pub fn get_candidates(&self, memory: &[u8], max: usize) -> Vec<SearchCandidate> {
+        self.candidates
+            .iter()
+            .take(max)
+            .filter_map(|address| {
+                Some(SearchCandidate {
+                    address: *address,
+                    value: self.options.read(memory, *address)?,
+                    previous: self.options.read(&self.snapshot, *address)?,
+                })
+            })
+            .collect()
+    }
+
+    #[doc = "
+        Cria um cheat de RAM que mantem `value` no endereço encontrado. O endereço e um índice
+        dentro de RETRO_MEMORY_SYSTEM_RAM, por isso o cheat ignora os mapas de memoria.
+    "]
+    pub fn create_cheat(&self, desc: &str, address: usize, value: i64) -> Cheat {
+        let mut cheat = Cheat::new_ram(
+            desc,
+            address,
+            value as u32,
+            self.options.size.get_bytes() as u32,
+        );
+        cheat.big_endian = self.options.big_endian;
+        cheat.system_ram = true;
+
+        cheat
+    }
+}
+
+#[cfg(test)]
+mod test_ram_search {
+    use crate::{
+        managers::cheat_manager::CheatManager,
+        memory::Memory,
+        ram_search::{RamSearch, SearchCompare, SearchOptions, SearchSize, SearchTarget},
+        retro_sys::{retro_memory_descriptor, retro_memory_map, RETRO_MEMDESC_SYSTEM_RAM},
+    };
+    use std::{os::raw::c_void, ptr::null};
+
+    #[test]
+    fn search_previous_and_value() {
+        let mut memory = vec![0u8; 16];
+        memory[3] = 5;
+        memory[7] = 5;
+
+        let mut search = RamSearch::new(&memory, SearchOptions::default());
+        assert_eq!(search.len(), 16);
+
+        assert_eq!(
+            search.search(&memory, SearchCompare::Equal, SearchTarget::Value(5)),
+            2
+        );
+
+        memory[3] = 4;
+        memory[7] = 6;
+
+        assert_eq!(
+            search.search(&memory, SearchCompare::Less, SearchTarget::Previous),
+            1
+        );
+
+        let candidates = search.get_candidates(&memory, 10);
+        assert_eq!(candidates[0].address, 3);
+        assert_eq!(candidates[0].value, 4);
+
+        let cheat = search.create_cheat("Vidas", candidates[0].address, 9);
+        assert_eq!(cheat.address, 3);
+        assert_eq!(cheat.size, 1);
+
+        search.reset(&memory);
+        assert_eq!(
+            search.search(&memory, SearchCompare::NotEqual, SearchTarget::Previous),
+            0
+        );
+    }
+
+    #[test]
+    fn search_sizes() {
+        let memory = [0xFF, 0xFE, 0x12, 0x34, 0x00, 0x00, 0x01, 0x00];
+
+        let options = SearchOptions {
+            size: SearchSize::Bits16,
+            signed: true,
+            big_endian: false,
+        };
+        assert_eq!(options.read(&memory, 0), Some(-257));
+
+        let options = SearchOptions {
+            size: SearchSize::Bits16,
+            signed: false,
+            big_endian: true,
+        };
+        assert_eq!(options.read(&memory, 2), Some(0x1234));
+        assert_eq!(options.read(&memory, 7), None);
+
+        let mut search = RamSearch::new(
+            &memory,
+            SearchOptions {
+                size: SearchSize::Bits32,
+                ..Default::default()
+            },
+        );
+
+        assert_eq!(search.len(), 2);
+        assert_eq!(
+            search.search(&memory, SearchCompare::Equal, SearchTarget::Value(0x10000)),
+            1
+        );
+        assert_eq!(search.get_candidates(&memory, 10)[0].address, 4);
+    }
+
+    #[test]
+    fn cheat_with_memory_maps() {
+        let mut ram = vec![0u8; 0x100];
+
+        //a RAM aparece no barramento emulado a partir de 0x7E0000
+        let descriptors = [retro_memory_descriptor {
+            flags: RETRO_MEMDESC_SYSTEM_RAM as u64,
+            ptr: ram.as_mut_ptr() as *mut c_void,
+            offset: 0,
+            start: 0x7E0000,
+            select: 0,
+            disconnect: 0,
+            len: ram.len(),
+            addrspace: null(),
+        }];
+
+        let memory = Memory::default();
+        unsafe {
+            memory.set_memory_maps(&retro_memory_map {
+                descriptors: descriptors.as_ptr(),
+                num_descriptors: descriptors.len() as u32,
+            })
+        };
+        memory.set_system_ram(ram.as_mut_ptr(), ram.len());
+
+        ram[0x10] = 3;
+        let mut search = RamSearch::new(&ram, SearchOptions::default());
+        search.search(&ram, SearchCompare::Equal, SearchTarget::Value(3));
+
+        let address = search.get_candidates(&ram, 1)[0].address;
+        let mut cheat = search.create_cheat("Vidas", address, 9);
+        cheat.enabled = true;
+
+        let cheats = CheatManager::new("retro_out_test/cheats", "test".to_string());
+        cheats.add_cheat(cheat);
+        cheats.apply_ram_cheats(&memory);
+
+        assert_eq!(ram[0x10], 9);
+        assert_eq!(memory.read_u8(0x7E0010).unwrap(), 9);
+    }
+}
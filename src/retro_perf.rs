@@ -3,43 +3,83 @@ use sdl2::sys::{
     SDL_HasSSE2, SDL_HasSSE3, SDL_HasSSE41, SDL_HasSSE42, SDL_bool,
 };
 
+use crate::environment::get_ctx_label;
 use crate::retro_sys::{
     retro_perf_counter, retro_perf_tick_t, retro_time_t, RETRO_SIMD_AVX, RETRO_SIMD_AVX2,
     RETRO_SIMD_MMX, RETRO_SIMD_SSE, RETRO_SIMD_SSE2, RETRO_SIMD_SSE3, RETRO_SIMD_SSE4,
     RETRO_SIMD_SSE42,
 };
+use crate::tools::ffi_tools::get_str_from_ptr;
+use log::debug;
+use std::sync::Mutex;
 
-static mut LAST_COUNTER: Option<*mut retro_perf_counter> = None;
+//endereços dos contadores registrados pelo núcleo, os contadores pertencem ao núcleo
+static COUNTERS: Mutex<Vec<usize>> = Mutex::new(Vec::new());
 
 pub unsafe extern "C" fn core_get_perf_counter() -> retro_perf_tick_t {
     SDL_GetPerformanceCounter() as retro_perf_tick_t
 }
 
 pub unsafe extern "C" fn core_perf_register(counter_raw: *mut retro_perf_counter) {
-    let mut counter = *counter_raw;
+    if counter_raw.is_null() {
+        return;
+    }
+
+    let counter = &mut *counter_raw;
     counter.registered = true;
-    LAST_COUNTER = Some(counter_raw);
+
+    let mut counters = COUNTERS.lock().unwrap();
+
+    if !counters.contains(&(counter_raw as usize)) {
+        counters.push(counter_raw as usize);
+    }
 }
 
 pub unsafe extern "C" fn core_perf_start(counter_raw: *mut retro_perf_counter) {
-    let mut counter = *counter_raw;
+    if counter_raw.is_null() {
+        return;
+    }
+
+    let counter = &mut *counter_raw;
+
     if counter.registered {
+        counter.call_cnt += 1;
         counter.start = core_get_perf_counter();
     }
 }
 
 pub unsafe extern "C" fn core_perf_stop(counter_raw: *mut retro_perf_counter) {
-    let mut counter = *counter_raw;
-    counter.total = core_get_perf_counter() - counter.start;
+    if counter_raw.is_null() {
+        return;
+    }
+
+    let counter = &mut *counter_raw;
+
+    if counter.registered {
+        counter.total += core_get_perf_counter() - counter.start;
+    }
 }
 
 pub unsafe extern "C" fn core_perf_log() {
-    if let Some(counter_raw) = LAST_COUNTER {
-        let counter = *counter_raw;
-        println!("[timer] {:?}", counter);
+    for counter_raw in COUNTERS.lock().unwrap().iter() {
+        let counter = &*(*counter_raw as *const retro_perf_counter);
+
+        debug!(
+            target: "retro_ab::perf",
+            "[{}] {}: {} chamadas, {} ticks",
+            get_ctx_label(),
+            get_str_from_ptr(counter.ident),
+            counter.call_cnt,
+            counter.total
+        );
     }
 }
 
+#[doc = "Os contadores deixam de existir quando a biblioteca do núcleo e descarregada."]
+pub fn clear_perf_counters() {
+    COUNTERS.lock().unwrap().clear();
+}
+
 pub unsafe extern "C" fn get_cpu_features() -> u64 {
     let mut cpu: u64 = 0;
 
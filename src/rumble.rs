@@ -0,0 +1,134 @@
+use crate::constants::MAX_CORE_PORTS;
+use crate::retro_sys::retro_rumble_effect;
+use std::sync::RwLock;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct RumbleState {
+    pub strong: u16,
+    pub weak: u16,
+}
+
+#[derive(Debug)]
+pub struct Rumble {
+    pub enabled: RwLock<bool>,
+    #[doc = "Escala aplicada a força pedida pelo núcleo, vai de 0.0 ate 1.0."]
+    pub intensity: RwLock<f32>,
+    #[doc = "Força pedida pelo núcleo em cada porta, sem aplicar [Rumble::intensity]."]
+    pub ports: RwLock<Vec<RumbleState>>,
+}
+
+impl Default for Rumble {
+    fn default() -> Self {
+        Rumble {
+            enabled: RwLock::new(true),
+            intensity: RwLock::new(1.0),
+            ports: RwLock::new(Vec::new()),
+        }
+    }
+}
+
+impl Rumble {
+    pub fn get_state(&self, port: u32) -> RumbleState {
+        self.ports
+            .read()
+            .unwrap()
+            .get(port as usize)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    #[doc = "Estado que deve ser enviado ao controle depois de aplicar enabled e intensity."]
+    pub fn get_scaled_state(&self, port: u32) -> RumbleState {
+        let state = self.get_state(port);
+
+        RumbleState {
+            strong: self.scale(state.strong),
+            weak: self.scale(state.weak),
+        }
+    }
+
+    pub fn scale(&self, strength: u16) -> u16 {
+        if !*self.enabled.read().unwrap() {
+            return 0;
+        }
+
+        let intensity = self.intensity.read().unwrap().clamp(0.0, 1.0);
+
+        (strength as f32 * intensity).round() as u16
+    }
+
+    pub fn set_intensity(&self, intensity: f32) {
+        *self.intensity.write().unwrap() = intensity.clamp(0.0, 1.0);
+    }
+
+    #[doc = "Retorna verdadeiro se o estado da porta mudou, portas acima de MAX_CORE_PORTS sao ignoradas."]
+    pub fn set_state(&self, port: u32, effect: retro_rumble_effect, strength: u16) -> bool {
+        if port as usize >= MAX_CORE_PORTS {
+            return false;
+        }
+
+        let mut ports = self.ports.write().unwrap();
+
+        if ports.len() <= port as usize {
+            ports.resize(port as usize + 1, RumbleState::default());
+        }
+
+        let state = &mut ports[port as usize];
+
+        let old = match effect {
+            retro_rumble_effect::RETRO_RUMBLE_STRONG => &mut state.strong,
+            retro_rumble_effect::RETRO_RUMBLE_WEAK => &mut state.weak,
+            _ => return false,
+        };
+
+        let changed = *old != strength;
+        *old = strength;
+
+        changed
+    }
+
+    pub fn clear(&self) {
+        self.ports.write().unwrap().clear();
+    }
+}
+
+#[cfg(test)]
+mod test_rumble {
+    use crate::{
+        retro_sys::retro_rumble_effect::{RETRO_RUMBLE_STRONG, RETRO_RUMBLE_WEAK},
+        rumble::{Rumble, RumbleState},
+    };
+
+    #[test]
+    fn track_state() {
+        let rumble = Rumble::default();
+
+        assert!(rumble.set_state(1, RETRO_RUMBLE_STRONG, 0xffff));
+        assert!(!rumble.set_state(1, RETRO_RUMBLE_STRONG, 0xffff));
+        assert!(rumble.set_state(1, RETRO_RUMBLE_WEAK, 0x8000));
+
+        assert_eq!(rumble.get_state(0), RumbleState::default());
+        assert!(!rumble.set_state(u32::MAX, RETRO_RUMBLE_STRONG, 0xffff));
+        assert_eq!(
+            rumble.get_state(1),
+            RumbleState {
+                strong: 0xffff,
+                weak: 0x8000
+            }
+        );
+    }
+
+    #[test]
+    fn scale_state() {
+        let rumble = Rumble::default();
+        rumble.set_state(0, RETRO_RUMBLE_STRONG, 1000);
+        rumble.set_intensity(0.5);
+
+        assert_eq!(rumble.get_scaled_state(0).strong, 500);
+
+        *rumble.enabled.write().unwrap() = false;
+
+        assert_eq!(rumble.get_scaled_state(0).strong, 0);
+        assert_eq!(rumble.get_state(0).strong, 1000);
+    }
+}
@@ -0,0 +1,210 @@
+use crate::constants::MAX_CORE_PORTS;
+use crate::retro_sys::{
+    retro_sensor_action, RETRO_SENSOR_ACCELEROMETER_X, RETRO_SENSOR_ACCELEROMETER_Y,
+    RETRO_SENSOR_ACCELEROMETER_Z, RETRO_SENSOR_GYROSCOPE_X, RETRO_SENSOR_GYROSCOPE_Y,
+    RETRO_SENSOR_GYROSCOPE_Z, RETRO_SENSOR_ILLUMINANCE,
+};
+use std::sync::RwLock;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SensorType {
+    Accelerometer,
+    Gyroscope,
+    Illuminance,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct SensorInput {
+    #[doc = "O núcleo so recebe os valores depois de habilitar o sensor."]
+    pub enabled: bool,
+    #[doc = "Frequência em Hz pedida pelo núcleo."]
+    pub rate: u32,
+    #[doc = "x, y e z para acelerômetro e giroscópio, para o sensor de luz apenas o primeiro e usado (lux)."]
+    pub values: [f32; 3],
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct PortSensors {
+    pub accelerometer: SensorInput,
+    pub gyroscope: SensorInput,
+    pub illuminance: SensorInput,
+}
+
+impl PortSensors {
+    fn get_mut(&mut self, sensor: SensorType) -> &mut SensorInput {
+        match sensor {
+            SensorType::Accelerometer => &mut self.accelerometer,
+            SensorType::Gyroscope => &mut self.gyroscope,
+            SensorType::Illuminance => &mut self.illuminance,
+        }
+    }
+
+    pub fn get(&self, sensor: SensorType) -> &SensorInput {
+        match sensor {
+            SensorType::Accelerometer => &self.accelerometer,
+            SensorType::Gyroscope => &self.gyroscope,
+            SensorType::Illuminance => &self.illuminance,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Sensors {
+    pub ports: RwLock<Vec<PortSensors>>,
+}
+
+impl Sensors {
+    pub fn get_port(&self, port: u32) -> PortSensors {
+        self.ports
+            .read()
+            .unwrap()
+            .get(port as usize)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    pub fn is_enabled(&self, port: u32, sensor: SensorType) -> bool {
+        self.get_port(port).get(sensor).enabled
+    }
+
+    //portas acima de MAX_CORE_PORTS sao ignoradas para o núcleo nao alocar memoria sem limite
+    fn update(&self, port: u32, sensor: SensorType, f: impl FnOnce(&mut SensorInput)) -> bool {
+        if port as usize >= MAX_CORE_PORTS {
+            return false;
+        }
+
+        let mut ports = self.ports.write().unwrap();
+
+        if ports.len() <= port as usize {
+            ports.resize(port as usize + 1, PortSensors::default());
+        }
+
+        f(ports[port as usize].get_mut(sensor));
+
+        true
+    }
+
+    #[doc = "Chamado pelo núcleo para habilitar ou desabilitar um sensor."]
+    pub fn set_state(&self, port: u32, action: retro_sensor_action, rate: u32) -> bool {
+        let (sensor, enabled) = match action {
+            retro_sensor_action::RETRO_SENSOR_ACCELEROMETER_ENABLE => {
+                (SensorType::Accelerometer, true)
+            }
+            retro_sensor_action::RETRO_SENSOR_ACCELEROMETER_DISABLE => {
+                (SensorType::Accelerometer, false)
+            }
+            retro_sensor_action::RETRO_SENSOR_GYROSCOPE_ENABLE => (SensorType::Gyroscope, true),
+            retro_sensor_action::RETRO_SENSOR_GYROSCOPE_DISABLE => (SensorType::Gyroscope, false),
+            retro_sensor_action::RETRO_SENSOR_ILLUMINANCE_ENABLE => (SensorType::Illuminance, true),
+            retro_sensor_action::RETRO_SENSOR_ILLUMINANCE_DISABLE => {
+                (SensorType::Illuminance, false)
+            }
+            _ => return false,
+        };
+
+        self.update(port, sensor, |input| {
+            input.enabled = enabled;
+            input.rate = rate;
+        })
+    }
+
+    #[doc = "Retorna 0.0 se o sensor nao foi habilitado pelo núcleo."]
+    pub fn get_input(&self, port: u32, id: u32) -> f32 {
+        let (sensor, axis) = match id {
+            RETRO_SENSOR_ACCELEROMETER_X => (SensorType::Accelerometer, 0),
+            RETRO_SENSOR_ACCELEROMETER_Y => (SensorType::Accelerometer, 1),
+            RETRO_SENSOR_ACCELEROMETER_Z => (SensorType::Accelerometer, 2),
+            RETRO_SENSOR_GYROSCOPE_X => (SensorType::Gyroscope, 0),
+            RETRO_SENSOR_GYROSCOPE_Y => (SensorType::Gyroscope, 1),
+            RETRO_SENSOR_GYROSCOPE_Z => (SensorType::Gyroscope, 2),
+            RETRO_SENSOR_ILLUMINANCE => (SensorType::Illuminance, 0),
+            _ => return 0.0,
+        };
+
+        let port_sensors = self.get_port(port);
+        let input = port_sensors.get(sensor);
+
+        if input.enabled {
+            input.values[axis]
+        } else {
+            0.0
+        }
+    }
+
+    #[doc = "Aceleração em m/s² de cada eixo."]
+    pub fn set_accelerometer(&self, port: u32, x: f32, y: f32, z: f32) {
+        self.update(port, SensorType::Accelerometer, |input| {
+            input.values = [x, y, z]
+        });
+    }
+
+    #[doc = "Velocidade angular em rad/s de cada eixo."]
+    pub fn set_gyroscope(&self, port: u32, x: f32, y: f32, z: f32) {
+        self.update(port, SensorType::Gyroscope, |input| {
+            input.values = [x, y, z]
+        });
+    }
+
+    pub fn set_illuminance(&self, port: u32, lux: f32) {
+        self.update(port, SensorType::Illuminance, |input| {
+            input.values = [lux, 0.0, 0.0]
+        });
+    }
+}
+
+#[cfg(test)]
+mod test_sensor {
+    use crate::{
+        retro_sys::{
+            retro_sensor_action, RETRO_SENSOR_ACCELEROMETER_X, RETRO_SENSOR_ACCELEROMETER_Z,
+            RETRO_SENSOR_GYROSCOPE_Y, RETRO_SENSOR_ILLUMINANCE,
+        },
+        sensor::{SensorType, Sensors},
+    };
+
+    #[test]
+    fn accelerometer() {
+        let sensors = Sensors::default();
+        sensors.set_accelerometer(0, 1.5, 0.0, -9.8);
+
+        //o núcleo ainda nao habilitou o sensor
+        assert_eq!(sensors.get_input(0, RETRO_SENSOR_ACCELEROMETER_X), 0.0);
+
+        assert!(sensors.set_state(
+            0,
+            retro_sensor_action::RETRO_SENSOR_ACCELEROMETER_ENABLE,
+            60
+        ));
+
+        assert!(sensors.is_enabled(0, SensorType::Accelerometer));
+        assert_eq!(sensors.get_port(0).accelerometer.rate, 60);
+        assert_eq!(sensors.get_input(0, RETRO_SENSOR_ACCELEROMETER_X), 1.5);
+        assert_eq!(sensors.get_input(0, RETRO_SENSOR_ACCELEROMETER_Z), -9.8);
+        assert_eq!(sensors.get_input(0, RETRO_SENSOR_GYROSCOPE_Y), 0.0);
+        assert_eq!(sensors.get_input(1, RETRO_SENSOR_ACCELEROMETER_X), 0.0);
+        assert!(!sensors.set_state(
+            u32::MAX,
+            retro_sensor_action::RETRO_SENSOR_ACCELEROMETER_ENABLE,
+            60
+        ));
+
+        sensors.set_state(
+            0,
+            retro_sensor_action::RETRO_SENSOR_ACCELEROMETER_DISABLE,
+            0,
+        );
+
+        assert_eq!(sensors.get_input(0, RETRO_SENSOR_ACCELEROMETER_X), 0.0);
+    }
+
+    #[test]
+    fn illuminance() {
+        let sensors = Sensors::default();
+        sensors.set_state(1, retro_sensor_action::RETRO_SENSOR_ILLUMINANCE_ENABLE, 10);
+        sensors.set_illuminance(1, 320.0);
+
+        assert_eq!(sensors.get_input(1, RETRO_SENSOR_ILLUMINANCE), 320.0);
+        assert_eq!(sensors.get_input(0, RETRO_SENSOR_ILLUMINANCE), 0.0);
+        assert_eq!(sensors.get_input(1, 99), 0.0);
+    }
+}
@@ -0,0 +1,131 @@
+use crate::retro_sys::{
+    retro_fastforwarding_override, retro_throttle_state, RETRO_THROTTLE_FAST_FORWARD,
+    RETRO_THROTTLE_FRAME_STEPPING, RETRO_THROTTLE_NONE, RETRO_THROTTLE_REWINDING,
+    RETRO_THROTTLE_SLOW_MOTION,
+};
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum SpeedState {
+    #[default]
+    Normal,
+    #[doc = "`rate` e o multiplicador da velocidade normal, 0.0 significa sem limite."]
+    FastForward {
+        rate: f32,
+    },
+    #[doc = "`rate` e o multiplicador da velocidade normal, deve ser menor que 1.0."]
+    SlowMotion {
+        rate: f32,
+    },
+    Rewinding,
+    FrameStepping,
+    Paused,
+}
+
+impl SpeedState {
+    pub fn is_fast_forward(&self) -> bool {
+        matches!(self, SpeedState::FastForward { .. })
+    }
+
+    #[doc = "Converte para o que o núcleo espera em RETRO_ENVIRONMENT_GET_THROTTLE_STATE."]
+    pub fn to_throttle_state(&self, fps: f64) -> retro_throttle_state {
+        let fps = fps as f32;
+
+        let (mode, rate) = match *self {
+            SpeedState::Normal => (RETRO_THROTTLE_NONE, fps),
+            SpeedState::FastForward { rate } => (RETRO_THROTTLE_FAST_FORWARD, fps * rate),
+            SpeedState::SlowMotion { rate } => (RETRO_THROTTLE_SLOW_MOTION, fps * rate),
+            SpeedState::Rewinding => (RETRO_THROTTLE_REWINDING, fps),
+            SpeedState::FrameStepping | SpeedState::Paused => (RETRO_THROTTLE_FRAME_STEPPING, 0.0),
+        };
+
+        retro_throttle_state { mode, rate }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct FastForwardOverride {
+    #[doc = "Multiplicador pedido pelo núcleo, 0.0 significa que o frontend escolhe."]
+    pub ratio: f32,
+    #[doc = "Se verdadeiro o núcleo quer que o frontend acelere, por exemplo durante uma tela de carregamento."]
+    pub fastforward: bool,
+    #[doc = "Se verdadeiro o frontend deve mostrar que esta acelerando."]
+    pub notification: bool,
+    #[doc = "Se verdadeiro o usuário nao pode mudar o fast-forward enquanto o override estiver ativo."]
+    pub inhibit_toggle: bool,
+}
+
+impl FastForwardOverride {
+    pub fn from_raw(raw: &retro_fastforwarding_override) -> Self {
+        FastForwardOverride {
+            ratio: raw.ratio,
+            fastforward: raw.fastforward,
+            notification: raw.notification,
+            inhibit_toggle: raw.inhibit_toggle,
+        }
+    }
+
+    #[doc = "Aplica o override do núcleo sobre o estado escolhido pelo frontend."]
+    pub fn apply(&self, state: SpeedState) -> SpeedState {
+        if self.fastforward {
+            SpeedState::FastForward { rate: self.ratio }
+        } else if self.inhibit_toggle && state.is_fast_forward() {
+            SpeedState::Normal
+        } else {
+            state
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_speed {
+    use crate::{
+        retro_sys::{
+            RETRO_THROTTLE_FAST_FORWARD, RETRO_THROTTLE_FRAME_STEPPING, RETRO_THROTTLE_NONE,
+        },
+        speed::{FastForwardOverride, SpeedState},
+    };
+
+    #[test]
+    fn throttle_state() {
+        let throttle = SpeedState::Normal.to_throttle_state(60.0);
+
+        assert_eq!(throttle.mode, RETRO_THROTTLE_NONE);
+        assert_eq!(throttle.rate, 60.0);
+
+        let throttle = SpeedState::FastForward { rate: 2.0 }.to_throttle_state(60.0);
+
+        assert_eq!(throttle.mode, RETRO_THROTTLE_FAST_FORWARD);
+        assert_eq!(throttle.rate, 120.0);
+
+        let throttle = SpeedState::Paused.to_throttle_state(60.0);
+
+        assert_eq!(throttle.mode, RETRO_THROTTLE_FRAME_STEPPING);
+        assert_eq!(throttle.rate, 0.0);
+    }
+
+    #[test]
+    fn fastforward_override() {
+        let loading = FastForwardOverride {
+            ratio: 0.0,
+            fastforward: true,
+            notification: false,
+            inhibit_toggle: true,
+        };
+
+        assert_eq!(
+            loading.apply(SpeedState::Normal),
+            SpeedState::FastForward { rate: 0.0 }
+        );
+
+        let inhibit = FastForwardOverride {
+            fastforward: false,
+            ..loading
+        };
+
+        assert_eq!(
+            inhibit.apply(SpeedState::FastForward { rate: 3.0 }),
+            SpeedState::Normal
+        );
+        assert_eq!(inhibit.apply(SpeedState::Rewinding), SpeedState::Rewinding);
+    }
+}
@@ -1,10 +1,7 @@
 use std::sync::RwLock;
 
 use crate::{
-    binding::binding_libretro::{
-        retro_subsystem_info, retro_subsystem_memory_info, retro_subsystem_rom_info,
-        retro_system_info, LibretroRaw,
-    },
+    binding::binding_libretro::{retro_subsystem_info, retro_system_info, LibretroRaw},
     constants::{MAX_CORE_SUBSYSTEM_INFO, MAX_CORE_SUBSYSTEM_ROM_INFO},
     controller_info::ControllerInfo,
     tools::{ffi_tools::get_str_from_ptr, mutex_tools::get_string_rwlock_from_ptr},
@@ -32,10 +29,22 @@ pub struct SubSystemRomInfo {
     pub need_full_path: RwLock<bool>,
     pub block_extract: RwLock<bool>,
     pub required: RwLock<bool>,
-    pub memory: MemoryInfo,
+    pub memory: Vec<MemoryInfo>,
     pub num_memory: RwLock<u32>,
 }
 
+impl SubSystemRomInfo {
+    pub fn is_valid_extension(&self, extension: &str) -> bool {
+        let extension = extension.to_lowercase();
+
+        self.valid_extensions
+            .read()
+            .unwrap()
+            .split('|')
+            .any(|valid| valid.to_lowercase() == extension)
+    }
+}
+
 #[derive(Default, Debug)]
 pub struct SubSystemInfo {
     pub id: RwLock<u32>,
@@ -49,6 +58,8 @@ pub struct System {
     pub ports: RwLock<Vec<ControllerInfo>>,
     pub info: SysInfo,
     pub subsystem: RwLock<Vec<SubSystemInfo>>,
+    #[doc = "Informado pelo núcleo em RETRO_ENVIRONMENT_SET_SUPPORT_ACHIEVEMENTS."]
+    pub support_achievements: RwLock<bool>,
 }
 
 impl System {
@@ -67,6 +78,7 @@ impl System {
             System {
                 ports: RwLock::new(Vec::new()),
                 subsystem: RwLock::new(Vec::new()),
+                support_achievements: RwLock::new(false),
                 info: SysInfo {
                     library_name: RwLock::new(get_str_from_ptr(sys_info.library_name)),
                     library_version: RwLock::new(get_str_from_ptr(sys_info.library_version)),
@@ -87,14 +99,20 @@ impl System {
                 *subsystem.desc.write().unwrap() = get_str_from_ptr(raw_sys.desc);
                 *subsystem.ident.write().unwrap() = get_str_from_ptr(raw_sys.ident);
 
-                let roms = unsafe {
-                    *(raw_sys.roms as *mut [retro_subsystem_rom_info; MAX_CORE_SUBSYSTEM_ROM_INFO])
-                };
+                for index in 0..raw_sys.num_roms.min(MAX_CORE_SUBSYSTEM_ROM_INFO as u32) {
+                    let rom = unsafe { *raw_sys.roms.add(index as usize) };
 
-                for index in 0..raw_sys.num_roms {
-                    let rom = roms[index as usize];
+                    //cada rom pode ter mais de um tipo de memoria, ex: SRAM e RTC
+                    let memory = (0..rom.num_memory)
+                        .map(|mem_index| {
+                            let memory = unsafe { *rom.memory.add(mem_index as usize) };
 
-                    let memory = unsafe { *(rom.memory as *mut retro_subsystem_memory_info) };
+                            MemoryInfo {
+                                extension: get_string_rwlock_from_ptr(memory.extension),
+                                type_: RwLock::new(memory.type_),
+                            }
+                        })
+                        .collect();
 
                     subsystem.roms.write().unwrap().push(SubSystemRomInfo {
                         desc: get_string_rwlock_from_ptr(rom.desc),
@@ -103,10 +121,7 @@ impl System {
                         block_extract: RwLock::new(rom.block_extract),
                         required: RwLock::new(rom.required),
                         num_memory: RwLock::new(rom.num_memory),
-                        memory: MemoryInfo {
-                            extension: get_string_rwlock_from_ptr(memory.extension),
-                            type_: RwLock::new(memory.type_),
-                        },
+                        memory,
                     });
                 }
 
@@ -121,7 +136,11 @@ impl System {
 //
 #[cfg(test)]
 mod test_system {
-    use crate::{system::System, test_tools};
+    use crate::{
+        system::{SubSystemRomInfo, System},
+        test_tools,
+    };
+    use std::sync::RwLock;
 
     #[test]
     fn test_get_sys_info() {
@@ -148,4 +167,16 @@ mod test_system {
 
         assert_eq!(*sys.info.need_full_path.read().unwrap(), false);
     }
+
+    #[test]
+    fn subsystem_rom_extension() {
+        let rom = SubSystemRomInfo {
+            valid_extensions: RwLock::new("gb|GBC".to_owned()),
+            ..Default::default()
+        };
+
+        assert!(rom.is_valid_extension("GB"));
+        assert!(rom.is_valid_extension("gbc"));
+        assert!(!rom.is_valid_extension("sfc"));
+    }
 }
@@ -1 +1,2 @@
 pub const CORE_TEST_RELATIVE_PATH: &str = "cores/test.dll";
+pub const ROM_TEST_RELATIVE_PATH: &str = "roms/test.sfc";
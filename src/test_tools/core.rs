@@ -1,7 +1,10 @@
 use crate::core::CoreWrapper;
-use crate::environment::RetroEnvCallbacks;
+use crate::environment::{RetroEnvCallbacks, RetroOptionalCallbacks};
 use crate::graphic_api::GraphicApi;
-use crate::retro_sys::retro_rumble_effect;
+use crate::memory::MemoryRegion;
+use crate::message::CoreMessage;
+use crate::retro_sys::{retro_log_level, retro_rumble_effect};
+use crate::speed::FastForwardOverride;
 use crate::test_tools::constants::CORE_TEST_RELATIVE_PATH;
 use crate::test_tools::paths::get_paths;
 use std::ptr;
@@ -40,6 +43,26 @@ fn rumble_callback(port: std::os::raw::c_uint, effect: retro_rumble_effect, stre
     true
 }
 
+fn led_callback(led: i32, state: i32) {
+    println!("led_callback -> led:{led} state:{state}");
+}
+
+fn message_callback(message: &CoreMessage) {
+    println!("message_callback -> {:?}", message);
+}
+
+fn log_callback(level: retro_log_level, message: &str) {
+    println!("log_callback -> [{:?}] {message}", level);
+}
+
+fn fastforwarding_override_callback(ff_override: &FastForwardOverride) {
+    println!("fastforwarding_override_callback -> {:?}", ff_override);
+}
+
+fn achievements_callback(regions: &[MemoryRegion]) {
+    println!("achievements_callback -> {} regiões", regions.len());
+}
+
 fn context_destroy() {
     println!("context_destroy");
 }
@@ -68,13 +91,26 @@ pub fn get_callbacks() -> RetroEnvCallbacks {
     }
 }
 
+pub fn get_optional_callbacks() -> RetroOptionalCallbacks {
+    RetroOptionalCallbacks {
+        led_callback: Some(led_callback),
+        message_callback: Some(message_callback),
+        log_callback: Some(log_callback),
+        fastforwarding_override_callback: Some(fastforwarding_override_callback),
+        achievements_callback: Some(achievements_callback),
+    }
+}
+
 pub fn get_core_wrapper() -> Arc<CoreWrapper> {
-    CoreWrapper::new(
+    let core = CoreWrapper::new(
         Uuid::new_v4(),
         CORE_TEST_RELATIVE_PATH,
         get_paths().unwrap(),
         get_callbacks(),
         GraphicApi::default(),
     )
-    .unwrap()
+    .unwrap();
+
+    core.set_optional_callbacks(get_optional_callbacks());
+    core
 }
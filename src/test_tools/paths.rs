@@ -7,5 +7,6 @@ pub fn get_paths() -> Result<Paths, ErroHandle> {
         "retro_out_test/save".to_string(),
         "retro_out_test/opt".to_string(),
         "retro_out_test/assents".to_string(),
-    )
+    )?
+    .with_cheats("retro_out_test/cheats".to_string())
 }
@@ -1,19 +1,19 @@
-use super::ffi_tools::make_c_string;
+use crate::archive::{self, ArchiveKind};
+use crate::content::{ContentInfoOverride, LoadedContent, MemoryFile};
 use crate::core::CoreWrapper;
+use crate::erro_handle::{ErroHandle, RetroLogLevel};
+use crate::patch::{self, PatchKind};
 use crate::retro_sys::retro_log_level;
-use crate::{
-    binding::binding_libretro::retro_game_info,
-    erro_handle::{ErroHandle, RetroLogLevel},
-};
+use log::warn;
 use std::fs;
 use std::io::Write;
 use std::{
-    ffi::CString,
     fs::File,
     io::Read,
     os::raw::c_void,
     path::{Path, PathBuf},
     ptr::null,
+    slice,
 };
 
 fn get_full_path(path: &str) -> Result<PathBuf, ErroHandle> {
@@ -26,15 +26,31 @@ fn get_full_path(path: &str) -> Result<PathBuf, ErroHandle> {
     }
 }
 
-fn valid_rom_extension(ctx: &CoreWrapper, path: &Path) -> Result<(), ErroHandle> {
-    let valid_extensions = ctx.system.info.valid_extensions.read().unwrap();
-    let path_str = path.extension().unwrap().to_str().unwrap();
+//`valid_extensions` segue o formato do libretro, ex: "sfc|smc"
+fn has_valid_extension(valid_extensions: &str, path: &Path) -> bool {
+    let extension = path
+        .extension()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_lowercase();
+
+    valid_extensions
+        .split('|')
+        .any(|valid| valid.to_lowercase() == extension)
+}
+
+fn valid_rom_extension(valid_extensions: &str, path: &Path) -> Result<(), ErroHandle> {
+    let path_str = path
+        .extension()
+        .unwrap_or_default()
+        .to_str()
+        .unwrap_or_default();
 
-    if !valid_extensions.contains(path_str) {
+    if !has_valid_extension(valid_extensions, path) {
         return Err(ErroHandle {
             level: RetroLogLevel::RETRO_LOG_ERROR,
             message: "Extensão da rom invalida: valores esperados -> ".to_string()
-                + &valid_extensions.to_string()
+                + valid_extensions
                 + "; valor recebido -> "
                 + path_str,
         });
@@ -60,59 +76,502 @@ fn get_save_path(ctx: &CoreWrapper, slot: usize) -> Result<PathBuf, ErroHandle>
 pub struct RomTools;
 
 impl RomTools {
-    pub fn create_game_info(ctx: &CoreWrapper, path: &str) -> Result<bool, ErroHandle> {
+    //retorna need_fullpath e persistent_data respeitando RETRO_ENVIRONMENT_SET_CONTENT_INFO_OVERRIDE
+    fn get_content_flags(ctx: &CoreWrapper, path: &Path) -> (bool, bool) {
+        let need_fullpath = *ctx.system.info.need_full_path.read().unwrap();
+
+        Self::get_content_flags_or(ctx, path, need_fullpath)
+    }
+
+    //`need_fullpath` e usado quando nenhum override cobre a extensão, cada rom de um subsystem tem o seu
+    fn get_content_flags_or(ctx: &CoreWrapper, path: &Path, need_fullpath: bool) -> (bool, bool) {
+        let extension = path
+            .extension()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_string();
+
+        let overrides = ctx.content_overrides.read().unwrap();
+
+        match ContentInfoOverride::find(&overrides, &extension) {
+            Some(content_override) => (
+                content_override.need_fullpath,
+                content_override.persistent_data,
+            ),
+            None => (need_fullpath, false),
+        }
+    }
+
+    #[doc = "Lê a rom respeitando RETRO_ENVIRONMENT_SET_CONTENT_INFO_OVERRIDE para a extensão dela."]
+    pub fn create_loaded_content(
+        ctx: &CoreWrapper,
+        path: &Path,
+    ) -> Result<LoadedContent, ErroHandle> {
+        let (need_fullpath, persistent_data) = Self::get_content_flags(ctx, path);
+
+        LoadedContent::new(path, need_fullpath, persistent_data)
+    }
+
+    //pasta das roms que o núcleo precisa ler do disco mas nao existem como arquivo.
+    //Fica dentro da pasta save para ser coberta pelo sandbox do vfs, e como o nome nao muda
+    //entre execuções os arquivos deixados por um crash sao sobrescritos no próximo load
+    fn get_temp_dir(ctx: &CoreWrapper) -> PathBuf {
+        Path::new(&ctx.paths.save)
+            .join("temp")
+            .join(&*ctx.system.info.library_name.read().unwrap())
+    }
+
+    #[doc = "
+        Extrai uma rom de um zip ou 7z. Sem `entry` o primeiro arquivo com uma extensão
+        aceita pelo núcleo e usado.
+    "]
+    pub fn create_archive_content(
+        ctx: &CoreWrapper,
+        path: &Path,
+        entry: Option<&str>,
+    ) -> Result<LoadedContent, ErroHandle> {
+        let valid_extensions = ctx.system.info.valid_extensions.read().unwrap().clone();
+        let need_fullpath = *ctx.system.info.need_full_path.read().unwrap();
+
+        Self::extract_archive_content(ctx, path, entry, &valid_extensions, need_fullpath)
+    }
+
+    fn extract_archive_content(
+        ctx: &CoreWrapper,
+        path: &Path,
+        entry: Option<&str>,
+        valid_extensions: &str,
+        need_fullpath: bool,
+    ) -> Result<LoadedContent, ErroHandle> {
+        let entries = archive::list_entries(path)?;
+
+        let entry = match entry {
+            Some(entry) if entries.iter().any(|name| name == entry) => entry.to_string(),
+            Some(entry) => {
+                return Err(ErroHandle {
+                    level: RetroLogLevel::RETRO_LOG_ERROR,
+                    message: "O arquivo ".to_string()
+                        + entry
+                        + " nao existe em "
+                        + &path.display().to_string(),
+                })
+            }
+            None => match entries
+                .into_iter()
+                .find(|name| has_valid_extension(valid_extensions, Path::new(name)))
+            {
+                Some(entry) => entry,
+                None => {
+                    return Err(ErroHandle {
+                        level: RetroLogLevel::RETRO_LOG_ERROR,
+                        message: "Nenhuma rom valida foi encontrada em ".to_string()
+                            + &path.display().to_string(),
+                    })
+                }
+            },
+        };
+
+        valid_rom_extension(valid_extensions, Path::new(&entry))?;
+
+        let data = archive::read_entry(path, &entry)?;
+        let (need_fullpath, persistent_data) =
+            Self::get_content_flags_or(ctx, Path::new(&entry), need_fullpath);
+
+        let temp_dir = Self::get_temp_dir(ctx);
+
+        if need_fullpath {
+            ctx.vfs.read().unwrap().allow_dir(&temp_dir);
+        }
+
+        LoadedContent::from_archive(
+            path,
+            &entry,
+            data,
+            need_fullpath,
+            persistent_data,
+            &temp_dir,
+        )
+    }
+
+    #[doc = "
+        Aplica um patch IPS, BPS ou UPS no buffer da rom. Sem `patch_path` procura um
+        `<rom>.ips`, `.bps` ou `.ups` na pasta da rom. O patch e recusado quando o núcleo
+        precisa do caminho da rom, ja que ele lê o arquivo original do disco.
+    "]
+    pub fn patch_content(
+        content: &mut LoadedContent,
+        patch_path: Option<&str>,
+    ) -> Result<(), ErroHandle> {
+        let patch_path = match patch_path {
+            Some(patch_path) => get_full_path(patch_path)?,
+            None => {
+                let dir = PathBuf::from(&*content.dir.to_string_lossy());
+
+                match patch::find_patch(&dir, &content.get_name()) {
+                    Some(patch_path) => patch_path,
+                    None => return Ok(()),
+                }
+            }
+        };
+
+        let kind = PatchKind::from_path(&patch_path).ok_or_else(|| ErroHandle {
+            level: RetroLogLevel::RETRO_LOG_ERROR,
+            message: "Formato de patch nao suportado: ".to_string()
+                + &patch_path.display().to_string(),
+        })?;
+
+        //o núcleo lê a rom direto do disco, o patch nao teria efeito
+        if content.need_fullpath {
+            return Err(ErroHandle {
+                level: RetroLogLevel::RETRO_LOG_ERROR,
+                message: "O núcleo precisa do caminho da rom, nao e possível aplicar o patch "
+                    .to_string()
+                    + &patch_path.display().to_string(),
+            });
+        }
+
+        let patch_data = fs::read(&patch_path).map_err(|e| ErroHandle {
+            level: RetroLogLevel::RETRO_LOG_ERROR,
+            message: e.to_string(),
+        })?;
+
+        content.data = patch::apply_patch(kind, &content.data, &patch_data)?;
+
+        Ok(())
+    }
+
+    #[doc = "
+        Arquivos zip e 7z sao extraídos antes de chegar ao núcleo, a nao ser que o núcleo
+        tenha pedido block_extract. Veja [RomTools::patch_content] para os patches.
+    "]
+    pub fn create_game_info(
+        ctx: &CoreWrapper,
+        path: &str,
+        entry: Option<&str>,
+        patch_path: Option<&str>,
+    ) -> Result<bool, ErroHandle> {
         let f_path = get_full_path(path)?;
+        let block_extract = *ctx.system.info.block_extract.read().unwrap();
+        let is_archive = ArchiveKind::from_path(&f_path).is_some();
+
+        let mut content = if is_archive && !block_extract {
+            Self::create_archive_content(ctx, &f_path, entry)?
+        } else {
+            valid_rom_extension(&ctx.system.info.valid_extensions.read().unwrap(), &f_path)?;
+            Self::create_loaded_content(ctx, &f_path)?
+        };
+
+        //com block_extract o núcleo recebe o zip/7z inteiro, um patch de rom nao se aplica
+        if is_archive && block_extract {
+            if let Some(patch_path) = patch_path {
+                return Err(ErroHandle {
+                    level: RetroLogLevel::RETRO_LOG_ERROR,
+                    message: "O núcleo recebe o arquivo compactado, nao e possível aplicar o patch "
+                        .to_string() + patch_path,
+                });
+            }
+
+            return Ok(Self::load_content(ctx, content));
+        }
+
+        match (patch_path, Self::patch_content(&mut content, patch_path)) {
+            (_, Ok(())) => {}
+            (Some(_), Err(e)) => return Err(e),
+            //um patch encontrado na pasta da rom nao impede o jogo de carregar
+            (None, Err(e)) => warn!("{}", e.message),
+        }
 
-        valid_rom_extension(ctx, &f_path)?;
+        Ok(Self::load_content(ctx, content))
+    }
+
+    #[doc = "
+        Carrega uma rom que so existe na memoria. O buffer fica em [CoreWrapper::loaded_content]
+        ate a rom ser descarregada.
+    "]
+    pub fn create_memory_game_info(
+        ctx: &CoreWrapper,
+        data: Vec<u8>,
+        virtual_path: &str,
+        meta: &str,
+    ) -> Result<bool, ErroHandle> {
+        let virtual_path = Path::new(virtual_path);
+        valid_rom_extension(
+            &ctx.system.info.valid_extensions.read().unwrap(),
+            virtual_path,
+        )?;
+
+        let (need_fullpath, persistent_data) = Self::get_content_flags(ctx, virtual_path);
+        let temp_dir = Self::get_temp_dir(ctx);
+
+        if need_fullpath {
+            ctx.vfs.read().unwrap().allow_dir(&temp_dir);
+        }
 
-        let mut buf = Vec::new();
-        let meta = CString::new("").unwrap();
-        let path = make_c_string(&f_path.to_str().unwrap())?;
-        let mut size = 0;
+        let content = LoadedContent::from_memory(
+            virtual_path,
+            data,
+            meta,
+            need_fullpath,
+            persistent_data,
+            &temp_dir,
+        )?;
+
+        Ok(Self::load_content(ctx, content))
+    }
+
+    fn load_content(ctx: &CoreWrapper, content: LoadedContent) -> bool {
+        let game_info = content.to_game_info();
+
+        //o núcleo pode pedir RETRO_ENVIRONMENT_GET_GAME_INFO_EXT dentro de retro_load_game
+        ctx.set_loaded_content(vec![content]);
+
+        let state = unsafe { ctx.raw.retro_load_game(&game_info) };
 
-        let need_full_path = *ctx.system.info.need_full_path.read().unwrap();
+        if !state {
+            ctx.set_loaded_content(Vec::new());
+        }
 
-        if !need_full_path {
-            let mut file = File::open(&f_path).unwrap();
+        state
+    }
 
-            size = file.metadata().unwrap().len() as usize;
+    #[doc = "
+        Carrega as roms de um subsystem com retro_load_game_special.
+
+        `paths` segue a ordem de [crate::system::SubSystemInfo::roms], uma rom opcional
+        pode ser enviada como \"\". Cada rom segue as próprias regras de block_extract e
+        need_fullpath, alem dos overrides de RETRO_ENVIRONMENT_SET_CONTENT_INFO_OVERRIDE.
+    "]
+    pub fn create_subsystem_game_info(
+        ctx: &CoreWrapper,
+        subsystem_ident: &str,
+        paths: &[&str],
+    ) -> Result<bool, ErroHandle> {
+        let subsystems = ctx.system.subsystem.read().unwrap();
+
+        let subsystem = match subsystems
+            .iter()
+            .find(|subsystem| *subsystem.ident.read().unwrap() == subsystem_ident)
+        {
+            Some(subsystem) => subsystem,
+            None => {
+                return Err(ErroHandle {
+                    level: RetroLogLevel::RETRO_LOG_ERROR,
+                    message: "O núcleo nao possui o subsystem ".to_string() + subsystem_ident,
+                })
+            }
+        };
 
-            buf = Vec::with_capacity(size);
+        let roms = subsystem.roms.read().unwrap();
 
-            file.read_to_end(&mut buf).unwrap();
+        if paths.len() > roms.len() {
+            return Err(ErroHandle {
+                level: RetroLogLevel::RETRO_LOG_ERROR,
+                message: "O subsystem ".to_string()
+                    + subsystem_ident
+                    + " aceita no máximo "
+                    + &roms.len().to_string()
+                    + " roms",
+            });
         }
 
-        let game_info = retro_game_info {
-            data: if buf.is_empty() {
-                null()
+        let mut contents = Vec::new();
+        let mut memory_files = Vec::new();
+
+        for (index, rom) in roms.iter().enumerate() {
+            let path = paths.get(index).copied().unwrap_or_default();
+
+            if path.is_empty() {
+                if *rom.required.read().unwrap() {
+                    return Err(ErroHandle {
+                        level: RetroLogLevel::RETRO_LOG_ERROR,
+                        message: "A rom ".to_string()
+                            + &rom.desc.read().unwrap()
+                            + " e obrigatória",
+                    });
+                }
+
+                contents.push(LoadedContent::empty());
+                continue;
+            }
+
+            let f_path = get_full_path(path)?;
+            let valid_extensions = rom.valid_extensions.read().unwrap();
+            let need_fullpath = *rom.need_full_path.read().unwrap();
+            let block_extract = *rom.block_extract.read().unwrap();
+
+            let content = if ArchiveKind::from_path(&f_path).is_some() && !block_extract {
+                Self::extract_archive_content(ctx, &f_path, None, &valid_extensions, need_fullpath)?
             } else {
-                buf.as_ptr() as *const c_void
-            },
-            meta: meta.as_ptr(),
-            path: path.as_ptr(),
-            size,
+                let extension = f_path.extension().unwrap_or_default().to_string_lossy();
+
+                if !rom.is_valid_extension(&extension) {
+                    return Err(ErroHandle {
+                        level: RetroLogLevel::RETRO_LOG_ERROR,
+                        message: "Extensão da rom invalida: valores esperados -> ".to_string()
+                            + &valid_extensions
+                            + "; valor recebido -> "
+                            + &extension,
+                    });
+                }
+
+                let (need_fullpath, persistent_data) =
+                    Self::get_content_flags_or(ctx, &f_path, need_fullpath);
+
+                LoadedContent::new(&f_path, need_fullpath, persistent_data)?
+            };
+
+            let rom_name = f_path.file_stem().unwrap_or_default().to_string_lossy();
+
+            for memory in &rom.memory {
+                let mut memory_path = PathBuf::from(&ctx.paths.save);
+                memory_path.push(rom_name.to_string() + "." + &memory.extension.read().unwrap());
+
+                memory_files.push(MemoryFile {
+                    id: *memory.type_.read().unwrap(),
+                    path: memory_path,
+                });
+            }
+
+            contents.push(content);
+        }
+
+        let id = *subsystem.id.read().unwrap();
+        drop(roms);
+        drop(subsystems);
+
+        let game_infos: Vec<_> = contents
+            .iter()
+            .map(|content| content.to_game_info())
+            .collect();
+
+        ctx.set_loaded_content(contents);
+
+        let state = unsafe {
+            ctx.raw
+                .retro_load_game_special(id, game_infos.as_ptr(), game_infos.len())
         };
 
-        let state = unsafe { ctx.raw.retro_load_game(&game_info) };
+        if state {
+            *ctx.memory_files.lock().unwrap() = memory_files;
+            *ctx.subsystem_id.lock().unwrap() = Some(id);
+
+            //o núcleo ja carregou as roms, sem as memorias o jogo começa sem save
+            if let Err(e) = Self::load_memory_files(ctx) {
+                warn!("{}", e.message);
+            }
+        } else {
+            ctx.set_loaded_content(Vec::new());
+        }
 
         Ok(state)
     }
 
-    pub fn get_rom_name(path: &PathBuf) -> Result<String, ErroHandle> {
-        let extension = path.extension().unwrap();
-        let name = path
-            .file_name()
-            .unwrap()
-            .to_str()
-            .to_owned()
+    #[doc = "
+        Descarrega e carrega de novo as roms em [CoreWrapper::loaded_content], o mais próximo
+        de desligar e ligar o console. As memorias dos subsystems sao preservadas.
+        Retorna falso se o núcleo recusar a rom, nesse caso ela ja foi descarregada.
+    "]
+    pub fn reload_game(ctx: &CoreWrapper) -> Result<bool, ErroHandle> {
+        Self::save_memory_files(ctx)?;
+
+        //os buffers continuam em ctx, o lock e liberado porque o núcleo pode pedir
+        //RETRO_ENVIRONMENT_GET_GAME_INFO_EXT dentro de retro_load_game
+        let game_infos: Vec<_> = ctx
+            .loaded_content
+            .lock()
             .unwrap()
-            .to_string()
-            .replace(extension.to_str().unwrap(), "");
+            .iter()
+            .map(|content| content.to_game_info())
+            .collect();
+
+        let subsystem_id = *ctx.subsystem_id.lock().unwrap();
 
-        Ok(name)
+        let state = unsafe {
+            ctx.raw.retro_unload_game();
+
+            match (subsystem_id, game_infos.first()) {
+                (Some(id), _) => {
+                    ctx.raw
+                        .retro_load_game_special(id, game_infos.as_ptr(), game_infos.len())
+                }
+                (None, Some(game_info)) => ctx.raw.retro_load_game(game_info),
+                (None, None) => ctx.raw.retro_load_game(null()),
+            }
+        };
+
+        if !state {
+            return Ok(false);
+        }
+
+        if let Err(e) = Self::load_memory_files(ctx) {
+            warn!("{}", e.message);
+        }
+
+        Ok(true)
     }
 
-    pub fn create_save_state(ctx: &CoreWrapper, slot: usize) -> Result<(), ErroHandle> {
+    //a memoria pertence ao núcleo e so e valida enquanto a rom estiver carregada
+    unsafe fn get_memory<'a>(ctx: &CoreWrapper, id: u32) -> Option<&'a mut [u8]> {
+        let data = ctx.raw.retro_get_memory_data(id);
+        let size = ctx.raw.retro_get_memory_size(id);
+
+        if data.is_null() || size == 0 {
+            None
+        } else {
+            Some(slice::from_raw_parts_mut(data as *mut u8, size))
+        }
+    }
+
+    #[doc = "Copia os arquivos de [CoreWrapper::memory_files] para as memorias do núcleo."]
+    pub fn load_memory_files(ctx: &CoreWrapper) -> Result<(), ErroHandle> {
+        for memory_file in ctx.memory_files.lock().unwrap().iter() {
+            if !memory_file.path.exists() {
+                continue;
+            }
+
+            let memory = match unsafe { Self::get_memory(ctx, memory_file.id) } {
+                Some(memory) => memory,
+                None => continue,
+            };
+
+            let data = fs::read(&memory_file.path).map_err(|e| ErroHandle {
+                level: RetroLogLevel::RETRO_LOG_ERROR,
+                message: e.to_string(),
+            })?;
+
+            let size = data.len().min(memory.len());
+            memory[..size].copy_from_slice(&data[..size]);
+        }
+
+        Ok(())
+    }
+
+    #[doc = "Salva as memorias do núcleo nos arquivos de [CoreWrapper::memory_files]."]
+    pub fn save_memory_files(ctx: &CoreWrapper) -> Result<(), ErroHandle> {
+        for memory_file in ctx.memory_files.lock().unwrap().iter() {
+            if let Some(memory) = unsafe { Self::get_memory(ctx, memory_file.id) } {
+                fs::write(&memory_file.path, memory).map_err(|e| ErroHandle {
+                    level: RetroLogLevel::RETRO_LOG_ERROR,
+                    message: e.to_string(),
+                })?;
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn get_rom_name(path: &PathBuf) -> Result<String, ErroHandle> {
+        match path.file_stem() {
+            Some(name) => Ok(name.to_string_lossy().to_string()),
+            None => Err(ErroHandle {
+                level: RetroLogLevel::RETRO_LOG_ERROR,
+                message: "Nao foi possível obter o nome da rom ".to_string()
+                    + &path.display().to_string(),
+            }),
+        }
+    }
+
+    pub fn get_state_data(ctx: &CoreWrapper) -> Result<Vec<u8>, ErroHandle> {
         let size = unsafe { ctx.raw.retro_serialize_size() };
         let mut data = vec![0u8; size];
 
@@ -128,6 +587,12 @@ impl RomTools {
             });
         }
 
+        Ok(data)
+    }
+
+    pub fn create_save_state(ctx: &CoreWrapper, slot: usize) -> Result<(), ErroHandle> {
+        let data = Self::get_state_data(ctx)?;
+
         let mut file = File::create(get_save_path(ctx, slot)?).unwrap();
         file.write(&data).unwrap();
 
@@ -142,6 +607,10 @@ impl RomTools {
         let mut buff = Vec::new();
         save_file.read_to_end(&mut buff).unwrap();
 
+        Self::set_state_data(ctx, &mut buff)
+    }
+
+    pub fn set_state_data(ctx: &CoreWrapper, buff: &mut [u8]) -> Result<(), ErroHandle> {
         let core_expect_size = unsafe { ctx.raw.retro_serialize_size() };
         let buffer_size = buff.len();
 
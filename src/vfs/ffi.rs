@@ -0,0 +1,406 @@
+use super::{VfsBackend, VfsFile, VfsOpenMode};
+use crate::{
+    environment::get_vfs_backend,
+    retro_sys::{
+        retro_vfs_dir_handle, retro_vfs_file_handle, retro_vfs_interface,
+        RETRO_VFS_SEEK_POSITION_CURRENT, RETRO_VFS_SEEK_POSITION_END,
+        RETRO_VFS_SEEK_POSITION_START, RETRO_VFS_STAT_IS_DIRECTORY, RETRO_VFS_STAT_IS_VALID,
+    },
+    tools::ffi_tools::get_str_from_ptr,
+};
+use std::{
+    ffi::CString,
+    io::{ErrorKind, Read, SeekFrom, Write},
+    os::raw::{c_char, c_int, c_uint, c_void},
+    path::PathBuf,
+    ptr::null_mut,
+};
+
+pub const VFS_INTERFACE_VERSION: u32 = 3;
+
+pub static VFS_INTERFACE: retro_vfs_interface = retro_vfs_interface {
+    get_path: Some(vfs_get_path),
+    open: Some(vfs_open),
+    close: Some(vfs_close),
+    size: Some(vfs_size),
+    tell: Some(vfs_tell),
+    seek: Some(vfs_seek),
+    read: Some(vfs_read),
+    write: Some(vfs_write),
+    flush: Some(vfs_flush),
+    remove: Some(vfs_remove),
+    rename: Some(vfs_rename),
+    truncate: Some(vfs_truncate),
+    stat: Some(vfs_stat),
+    mkdir: Some(vfs_mkdir),
+    opendir: Some(vfs_opendir),
+    readdir: Some(vfs_readdir),
+    dirent_get_name: Some(vfs_dirent_get_name),
+    dirent_is_dir: Some(vfs_dirent_is_dir),
+    closedir: Some(vfs_closedir),
+};
+
+//o núcleo recebe apenas um ponteiro opaco para essas estruturas
+struct FileHandle {
+    path: CString,
+    file: Box<dyn VfsFile>,
+}
+
+struct DirHandle {
+    entries: Vec<(CString, bool)>,
+    current: Option<usize>,
+}
+
+fn get_path(path: *const c_char) -> Option<PathBuf> {
+    if path.is_null() {
+        return None;
+    }
+
+    Some(PathBuf::from(get_str_from_ptr(path)))
+}
+
+unsafe fn get_file<'a>(stream: *mut retro_vfs_file_handle) -> Option<&'a mut FileHandle> {
+    (stream as *mut FileHandle).as_mut()
+}
+
+unsafe fn get_dir<'a>(dirstream: *mut retro_vfs_dir_handle) -> Option<&'a mut DirHandle> {
+    (dirstream as *mut DirHandle).as_mut()
+}
+
+pub(crate) fn open_file(
+    backend: &dyn VfsBackend,
+    path: *const c_char,
+    mode: c_uint,
+) -> *mut retro_vfs_file_handle {
+    let (path, c_path) = match get_path(path) {
+        Some(path) => match CString::new(path.to_string_lossy().as_bytes()) {
+            Ok(c_path) => (path, c_path),
+            Err(_) => return null_mut(),
+        },
+        None => return null_mut(),
+    };
+
+    match backend.open(&path, VfsOpenMode::from_raw(mode)) {
+        Ok(file) => {
+            Box::into_raw(Box::new(FileHandle { path: c_path, file })) as *mut retro_vfs_file_handle
+        }
+        Err(_) => null_mut(),
+    }
+}
+
+pub(crate) fn open_dir(
+    backend: &dyn VfsBackend,
+    dir: *const c_char,
+    include_hidden: bool,
+) -> *mut retro_vfs_dir_handle {
+    let dir = match get_path(dir) {
+        Some(dir) => dir,
+        None => return null_mut(),
+    };
+
+    match backend.read_dir(&dir, include_hidden) {
+        Ok(entries) => {
+            let entries = entries
+                .into_iter()
+                .filter_map(|entry| Some((CString::new(entry.name).ok()?, entry.is_dir)))
+                .collect();
+
+            Box::into_raw(Box::new(DirHandle {
+                entries,
+                current: None,
+            })) as *mut retro_vfs_dir_handle
+        }
+        Err(_) => null_mut(),
+    }
+}
+
+unsafe extern "C" fn vfs_get_path(stream: *mut retro_vfs_file_handle) -> *const c_char {
+    match get_file(stream) {
+        Some(handle) => handle.path.as_ptr(),
+        None => std::ptr::null(),
+    }
+}
+
+unsafe extern "C" fn vfs_open(
+    path: *const c_char,
+    mode: c_uint,
+    _hints: c_uint,
+) -> *mut retro_vfs_file_handle {
+    match get_vfs_backend() {
+        Some(backend) => open_file(backend.as_ref(), path, mode),
+        None => null_mut(),
+    }
+}
+
+unsafe extern "C" fn vfs_close(stream: *mut retro_vfs_file_handle) -> c_int {
+    if stream.is_null() {
+        return -1;
+    }
+
+    let mut handle = Box::from_raw(stream as *mut FileHandle);
+
+    match handle.file.flush() {
+        Ok(_) => 0,
+        Err(_) => -1,
+    }
+}
+
+unsafe extern "C" fn vfs_size(stream: *mut retro_vfs_file_handle) -> i64 {
+    match get_file(stream).map(|handle| handle.file.size()) {
+        Some(Ok(size)) => size as i64,
+        _ => -1,
+    }
+}
+
+unsafe extern "C" fn vfs_tell(stream: *mut retro_vfs_file_handle) -> i64 {
+    match get_file(stream).map(|handle| handle.file.stream_position()) {
+        Some(Ok(position)) => position as i64,
+        _ => -1,
+    }
+}
+
+unsafe extern "C" fn vfs_seek(
+    stream: *mut retro_vfs_file_handle,
+    offset: i64,
+    seek_position: c_int,
+) -> i64 {
+    let seek_from = match seek_position as u32 {
+        RETRO_VFS_SEEK_POSITION_START if offset >= 0 => SeekFrom::Start(offset as u64),
+        RETRO_VFS_SEEK_POSITION_CURRENT => SeekFrom::Current(offset),
+        RETRO_VFS_SEEK_POSITION_END => SeekFrom::End(offset),
+        _ => return -1,
+    };
+
+    match get_file(stream).map(|handle| handle.file.seek(seek_from)) {
+        Some(Ok(position)) => position as i64,
+        _ => -1,
+    }
+}
+
+unsafe extern "C" fn vfs_read(stream: *mut retro_vfs_file_handle, s: *mut c_void, len: u64) -> i64 {
+    let handle = match get_file(stream) {
+        Some(handle) if !s.is_null() => handle,
+        _ => return -1,
+    };
+
+    let buf = std::slice::from_raw_parts_mut(s as *mut u8, len as usize);
+    let mut total = 0;
+
+    //read pode devolver menos bytes que o pedido mesmo antes do fim do arquivo
+    while total < buf.len() {
+        match handle.file.read(&mut buf[total..]) {
+            Ok(0) => break,
+            Ok(size) => total += size,
+            Err(e) if e.kind() == ErrorKind::Interrupted => {}
+            Err(_) => return -1,
+        }
+    }
+
+    total as i64
+}
+
+unsafe extern "C" fn vfs_write(
+    stream: *mut retro_vfs_file_handle,
+    s: *const c_void,
+    len: u64,
+) -> i64 {
+    let handle = match get_file(stream) {
+        Some(handle) if !s.is_null() => handle,
+        _ => return -1,
+    };
+
+    let buf = std::slice::from_raw_parts(s as *const u8, len as usize);
+
+    match handle.file.write_all(buf) {
+        Ok(_) => len as i64,
+        Err(_) => -1,
+    }
+}
+
+unsafe extern "C" fn vfs_flush(stream: *mut retro_vfs_file_handle) -> c_int {
+    match get_file(stream).map(|handle| handle.file.flush()) {
+        Some(Ok(_)) => 0,
+        _ => -1,
+    }
+}
+
+unsafe extern "C" fn vfs_remove(path: *const c_char) -> c_int {
+    match (get_vfs_backend(), get_path(path)) {
+        (Some(backend), Some(path)) if backend.remove(&path).is_ok() => 0,
+        _ => -1,
+    }
+}
+
+unsafe extern "C" fn vfs_rename(old_path: *const c_char, new_path: *const c_char) -> c_int {
+    match (get_vfs_backend(), get_path(old_path), get_path(new_path)) {
+        (Some(backend), Some(old_path), Some(new_path))
+            if backend.rename(&old_path, &new_path).is_ok() =>
+        {
+            0
+        }
+        _ => -1,
+    }
+}
+
+unsafe extern "C" fn vfs_truncate(stream: *mut retro_vfs_file_handle, length: i64) -> i64 {
+    if length < 0 {
+        return -1;
+    }
+
+    match get_file(stream).map(|handle| handle.file.truncate(length as u64)) {
+        Some(Ok(_)) => 0,
+        _ => -1,
+    }
+}
+
+unsafe extern "C" fn vfs_stat(path: *const c_char, size: *mut i32) -> c_int {
+    let stat = match (get_vfs_backend(), get_path(path)) {
+        (Some(backend), Some(path)) => backend.stat(&path),
+        _ => None,
+    };
+
+    match stat {
+        Some(stat) => {
+            if !size.is_null() {
+                *size = stat.size.min(i32::MAX as u64) as i32;
+            }
+
+            let mut flags = RETRO_VFS_STAT_IS_VALID;
+
+            if stat.is_dir {
+                flags |= RETRO_VFS_STAT_IS_DIRECTORY;
+            }
+
+            flags as c_int
+        }
+        None => 0,
+    }
+}
+
+unsafe extern "C" fn vfs_mkdir(dir: *const c_char) -> c_int {
+    let (backend, dir) = match (get_vfs_backend(), get_path(dir)) {
+        (Some(backend), Some(dir)) => (backend, dir),
+        _ => return -1,
+    };
+
+    match backend.mkdir(&dir) {
+        Ok(_) => 0,
+        Err(e) if e.kind() == ErrorKind::AlreadyExists => -2,
+        Err(_) => -1,
+    }
+}
+
+unsafe extern "C" fn vfs_opendir(
+    dir: *const c_char,
+    include_hidden: bool,
+) -> *mut retro_vfs_dir_handle {
+    match get_vfs_backend() {
+        Some(backend) => open_dir(backend.as_ref(), dir, include_hidden),
+        None => null_mut(),
+    }
+}
+
+unsafe extern "C" fn vfs_readdir(dirstream: *mut retro_vfs_dir_handle) -> bool {
+    match get_dir(dirstream) {
+        Some(handle) => {
+            let next = handle.current.map_or(0, |current| current + 1);
+            handle.current = Some(next);
+
+            next < handle.entries.len()
+        }
+        None => false,
+    }
+}
+
+unsafe extern "C" fn vfs_dirent_get_name(dirstream: *mut retro_vfs_dir_handle) -> *const c_char {
+    match get_dir(dirstream).and_then(|handle| handle.entries.get(handle.current?)) {
+        Some((name, _)) => name.as_ptr(),
+        None => std::ptr::null(),
+    }
+}
+
+unsafe extern "C" fn vfs_dirent_is_dir(dirstream: *mut retro_vfs_dir_handle) -> bool {
+    match get_dir(dirstream).and_then(|handle| handle.entries.get(handle.current?)) {
+        Some((_, is_dir)) => *is_dir,
+        None => false,
+    }
+}
+
+unsafe extern "C" fn vfs_closedir(dirstream: *mut retro_vfs_dir_handle) -> c_int {
+    if dirstream.is_null() {
+        return -1;
+    }
+
+    drop(Box::from_raw(dirstream as *mut DirHandle));
+
+    0
+}
+
+#[cfg(test)]
+mod test_vfs_ffi {
+    use super::{
+        open_dir, open_file, vfs_close, vfs_closedir, vfs_dirent_get_name, vfs_dirent_is_dir,
+        vfs_get_path, vfs_read, vfs_readdir, vfs_seek, vfs_size, vfs_tell, vfs_truncate, vfs_write,
+    };
+    use crate::{
+        retro_sys::{
+            RETRO_VFS_FILE_ACCESS_READ, RETRO_VFS_FILE_ACCESS_READ_WRITE,
+            RETRO_VFS_SEEK_POSITION_END, RETRO_VFS_SEEK_POSITION_START,
+        },
+        tools::ffi_tools::{get_str_from_ptr, make_c_string},
+        vfs::MemoryVfs,
+    };
+    use std::{os::raw::c_void, path::Path};
+
+    #[test]
+    fn file_handle() {
+        let vfs = MemoryVfs::default();
+        let path = make_c_string("save/game.srm").unwrap();
+
+        unsafe {
+            assert!(open_file(&vfs, path.as_ptr(), RETRO_VFS_FILE_ACCESS_READ).is_null());
+
+            let stream = open_file(&vfs, path.as_ptr(), RETRO_VFS_FILE_ACCESS_READ_WRITE);
+
+            assert!(!stream.is_null());
+            assert_eq!(get_str_from_ptr(vfs_get_path(stream)), "save/game.srm");
+
+            let data = b"retro_ab";
+            assert_eq!(vfs_write(stream, data.as_ptr() as *const c_void, 8), 8);
+            assert_eq!(vfs_size(stream), 8);
+            assert_eq!(vfs_seek(stream, -2, RETRO_VFS_SEEK_POSITION_END as i32), 6);
+
+            let mut buf = [0u8; 4];
+            assert_eq!(vfs_read(stream, buf.as_mut_ptr() as *mut c_void, 4), 2);
+            assert_eq!(&buf[..2], b"ab");
+            assert_eq!(vfs_tell(stream), 8);
+
+            assert_eq!(
+                vfs_seek(stream, -1, RETRO_VFS_SEEK_POSITION_START as i32),
+                -1
+            );
+            assert_eq!(vfs_truncate(stream, 5), 0);
+            assert_eq!(vfs_close(stream), 0);
+        }
+
+        assert_eq!(vfs.get_file(Path::new("save/game.srm")).unwrap(), b"retro");
+    }
+
+    #[test]
+    fn dir_handle() {
+        let vfs = MemoryVfs::default();
+        vfs.insert_file(Path::new("system/bios/scph.bin"), vec![]);
+        let dir = make_c_string("system").unwrap();
+
+        unsafe {
+            let dirstream = open_dir(&vfs, dir.as_ptr(), true);
+
+            assert!(!dirstream.is_null());
+            assert!(vfs_readdir(dirstream));
+            assert_eq!(get_str_from_ptr(vfs_dirent_get_name(dirstream)), "bios");
+            assert!(vfs_dirent_is_dir(dirstream));
+            assert!(!vfs_readdir(dirstream));
+            assert_eq!(vfs_closedir(dirstream), 0);
+        }
+    }
+}
@@ -0,0 +1,351 @@
+use super::{is_hidden, normalize_path, VfsBackend, VfsDirEntry, VfsFile, VfsOpenMode, VfsStat};
+use std::{
+    collections::{HashMap, HashSet},
+    io::{self, Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+type FileData = Arc<Mutex<Vec<u8>>>;
+
+#[doc = "
+    # Backend em memória
+
+    Nada e escrito no disco, útil para testes e para entregar ao núcleo
+    arquivos que foram extraídos de outro lugar. As pastas que contem
+    arquivos existem mesmo sem um mkdir.
+"]
+#[derive(Debug, Default)]
+pub struct MemoryVfs {
+    files: Mutex<HashMap<PathBuf, FileData>>,
+    dirs: Mutex<HashSet<PathBuf>>,
+}
+
+impl MemoryVfs {
+    pub fn insert_file(&self, path: &Path, data: Vec<u8>) {
+        self.files
+            .lock()
+            .unwrap()
+            .insert(normalize_path(path), Arc::new(Mutex::new(data)));
+    }
+
+    pub fn get_file(&self, path: &Path) -> Option<Vec<u8>> {
+        self.files
+            .lock()
+            .unwrap()
+            .get(&normalize_path(path))
+            .map(|data| data.lock().unwrap().clone())
+    }
+
+    fn is_dir(&self, path: &Path) -> bool {
+        if path.as_os_str().is_empty() || path.parent().is_none() {
+            return true;
+        }
+
+        let dirs = self.dirs.lock().unwrap();
+
+        dirs.iter().any(|dir| dir.starts_with(path))
+            || self
+                .files
+                .lock()
+                .unwrap()
+                .keys()
+                .any(|file| file != path && file.starts_with(path))
+    }
+}
+
+fn not_found() -> io::Error {
+    io::Error::new(io::ErrorKind::NotFound, "O arquivo nao existe")
+}
+
+impl VfsBackend for MemoryVfs {
+    fn open(&self, path: &Path, mode: VfsOpenMode) -> io::Result<Box<dyn VfsFile>> {
+        let path = normalize_path(path);
+        let mut files = self.files.lock().unwrap();
+
+        let data = match files.get(&path) {
+            Some(data) => {
+                if mode.write && !mode.update_existing {
+                    data.lock().unwrap().clear();
+                }
+
+                data.clone()
+            }
+            None if mode.write && !mode.update_existing => {
+                let data: FileData = Arc::default();
+                files.insert(path, data.clone());
+
+                data
+            }
+            None => return Err(not_found()),
+        };
+
+        Ok(Box::new(MemoryFile {
+            data,
+            position: 0,
+            mode,
+        }))
+    }
+
+    fn remove(&self, path: &Path) -> io::Result<()> {
+        let path = normalize_path(path);
+
+        if self.files.lock().unwrap().remove(&path).is_some()
+            || self.dirs.lock().unwrap().remove(&path)
+        {
+            Ok(())
+        } else {
+            Err(not_found())
+        }
+    }
+
+    fn rename(&self, old_path: &Path, new_path: &Path) -> io::Result<()> {
+        let mut files = self.files.lock().unwrap();
+        let data = files
+            .remove(&normalize_path(old_path))
+            .ok_or_else(not_found)?;
+        files.insert(normalize_path(new_path), data);
+
+        Ok(())
+    }
+
+    fn stat(&self, path: &Path) -> Option<VfsStat> {
+        let path = normalize_path(path);
+
+        if let Some(data) = self.files.lock().unwrap().get(&path) {
+            return Some(VfsStat {
+                is_dir: false,
+                size: data.lock().unwrap().len() as u64,
+            });
+        }
+
+        if self.is_dir(&path) {
+            Some(VfsStat {
+                is_dir: true,
+                size: 0,
+            })
+        } else {
+            None
+        }
+    }
+
+    fn mkdir(&self, path: &Path) -> io::Result<()> {
+        let path = normalize_path(path);
+
+        if self.stat(&path).is_some() {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                "A pasta ja existe",
+            ));
+        }
+
+        self.dirs.lock().unwrap().insert(path);
+
+        Ok(())
+    }
+
+    fn read_dir(&self, path: &Path, include_hidden: bool) -> io::Result<Vec<VfsDirEntry>> {
+        let path = normalize_path(path);
+
+        if !self.is_dir(&path) {
+            return Err(not_found());
+        }
+
+        let files: Vec<PathBuf> = self.files.lock().unwrap().keys().cloned().collect();
+        let dirs: Vec<PathBuf> = self.dirs.lock().unwrap().iter().cloned().collect();
+        let mut entries: Vec<VfsDirEntry> = Vec::new();
+
+        for (entry_path, is_file) in files
+            .iter()
+            .map(|f| (f, true))
+            .chain(dirs.iter().map(|d| (d, false)))
+        {
+            let relative = match entry_path.strip_prefix(&path) {
+                Ok(relative) => relative,
+                Err(_) => continue,
+            };
+
+            let mut components = relative.components();
+            let name = match components.next() {
+                Some(name) => name.as_os_str().to_string_lossy().to_string(),
+                None => continue,
+            };
+
+            if (!include_hidden && is_hidden(&name)) || entries.iter().any(|e| e.name == name) {
+                continue;
+            }
+
+            entries.push(VfsDirEntry {
+                name,
+                is_dir: !is_file || components.next().is_some(),
+            });
+        }
+
+        Ok(entries)
+    }
+}
+
+struct MemoryFile {
+    data: FileData,
+    position: u64,
+    mode: VfsOpenMode,
+}
+
+impl Read for MemoryFile {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if !self.mode.read {
+            return Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                "O arquivo nao foi aberto para leitura",
+            ));
+        }
+
+        let data = self.data.lock().unwrap();
+        let start = (self.position as usize).min(data.len());
+        let size = buf.len().min(data.len() - start);
+
+        buf[..size].copy_from_slice(&data[start..start + size]);
+        self.position += size as u64;
+
+        Ok(size)
+    }
+}
+
+impl Write for MemoryFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if !self.mode.write {
+            return Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                "O arquivo nao foi aberto para escrita",
+            ));
+        }
+
+        let mut data = self.data.lock().unwrap();
+        let start = self.position as usize;
+        let end = start + buf.len();
+
+        if data.len() < end {
+            data.resize(end, 0);
+        }
+
+        data[start..end].copy_from_slice(buf);
+        self.position = end as u64;
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Seek for MemoryFile {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let position = match pos {
+            SeekFrom::Start(offset) => offset as i64,
+            SeekFrom::Current(offset) => self.position as i64 + offset,
+            SeekFrom::End(offset) => self.data.lock().unwrap().len() as i64 + offset,
+        };
+
+        if position < 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Posição invalida",
+            ));
+        }
+
+        self.position = position as u64;
+
+        Ok(self.position)
+    }
+}
+
+impl VfsFile for MemoryFile {
+    fn size(&mut self) -> io::Result<u64> {
+        Ok(self.data.lock().unwrap().len() as u64)
+    }
+
+    fn truncate(&mut self, length: u64) -> io::Result<()> {
+        self.data.lock().unwrap().resize(length as usize, 0);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test_memory {
+    use crate::vfs::{MemoryVfs, VfsBackend, VfsOpenMode};
+    use std::{
+        io::{Read, Seek, SeekFrom, Write},
+        path::Path,
+    };
+
+    #[test]
+    fn read_write_seek() {
+        let vfs = MemoryVfs::default();
+        let path = Path::new("saves/game.srm");
+
+        let mut file = vfs.open(path, VfsOpenMode::from_raw(3)).unwrap();
+        file.write_all(b"abcdef").unwrap();
+        file.seek(SeekFrom::Start(2)).unwrap();
+        file.write_all(b"XY").unwrap();
+
+        assert_eq!(file.seek(SeekFrom::End(-1)).unwrap(), 5);
+
+        file.truncate(4).unwrap();
+
+        assert_eq!(file.size().unwrap(), 4);
+        assert_eq!(vfs.get_file(path).unwrap(), b"abXY");
+
+        let mut data = Vec::new();
+        let mut file = vfs.open(path, VfsOpenMode::from_raw(1)).unwrap();
+        file.read_to_end(&mut data).unwrap();
+
+        assert_eq!(data, b"abXY");
+        assert!(file.write_all(b"z").is_err());
+
+        //WRITE sem UPDATE_EXISTING trunca o arquivo
+        vfs.open(path, VfsOpenMode::from_raw(2)).unwrap();
+        assert_eq!(vfs.stat(path).unwrap().size, 0);
+
+        assert!(vfs
+            .open(Path::new("nada"), VfsOpenMode::from_raw(1))
+            .is_err());
+        assert!(vfs
+            .open(Path::new("nada"), VfsOpenMode::from_raw(2 | 4))
+            .is_err());
+    }
+
+    #[test]
+    fn directories() {
+        let vfs = MemoryVfs::default();
+        vfs.insert_file(Path::new("system/bios/scph.bin"), vec![0; 16]);
+        vfs.insert_file(Path::new("system/.hidden"), vec![]);
+        vfs.mkdir(Path::new("system/empty")).unwrap();
+
+        assert!(vfs.mkdir(Path::new("system/bios")).is_err());
+        assert!(vfs.stat(Path::new("system/bios")).unwrap().is_dir);
+        assert_eq!(
+            vfs.stat(Path::new("system/bios/scph.bin")).unwrap().size,
+            16
+        );
+        assert!(vfs.stat(Path::new("system/nada")).is_none());
+
+        let mut entries = vfs.read_dir(Path::new("./system"), false).unwrap();
+        entries.sort_by(|a, b| a.name.cmp(&b.name));
+
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].name, "bios");
+        assert!(entries[0].is_dir);
+        assert_eq!(entries[1].name, "empty");
+
+        vfs.rename(
+            Path::new("system/bios/scph.bin"),
+            Path::new("system/scph.bin"),
+        )
+        .unwrap();
+
+        assert!(vfs.stat(Path::new("system/bios")).is_none());
+        assert_eq!(vfs.read_dir(Path::new("system"), true).unwrap().len(), 3);
+    }
+}
@@ -0,0 +1,99 @@
+//! # VFS
+//!
+//! Implementação da interface de arquivos virtuais (versão 3) do libretro.
+//! Quando o núcleo pede `RETRO_ENVIRONMENT_GET_VFS_INTERFACE` todo acesso a arquivos
+//! feito por ele passa pelo [VfsBackend] escolhido em [crate::core::CoreWrapper::set_vfs].
+
+pub(crate) mod ffi;
+pub mod memory;
+pub mod std_fs;
+
+use crate::retro_sys::{
+    RETRO_VFS_FILE_ACCESS_READ, RETRO_VFS_FILE_ACCESS_UPDATE_EXISTING, RETRO_VFS_FILE_ACCESS_WRITE,
+};
+use std::{
+    io::{self, Read, Seek, Write},
+    path::{Component, Path, PathBuf},
+};
+
+pub use memory::MemoryVfs;
+pub use std_fs::StdFsVfs;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VfsOpenMode {
+    pub read: bool,
+    pub write: bool,
+    #[doc = "Se verdadeiro o arquivo precisa existir e nao e truncado ao abrir para escrita."]
+    pub update_existing: bool,
+}
+
+impl VfsOpenMode {
+    pub fn from_raw(mode: u32) -> Self {
+        VfsOpenMode {
+            read: mode & RETRO_VFS_FILE_ACCESS_READ != 0,
+            write: mode & RETRO_VFS_FILE_ACCESS_WRITE != 0,
+            update_existing: mode & RETRO_VFS_FILE_ACCESS_UPDATE_EXISTING != 0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VfsStat {
+    pub is_dir: bool,
+    pub size: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VfsDirEntry {
+    pub name: String,
+    pub is_dir: bool,
+}
+
+pub trait VfsFile: Read + Write + Seek + Send {
+    fn size(&mut self) -> io::Result<u64>;
+
+    fn truncate(&mut self, length: u64) -> io::Result<()>;
+}
+
+pub trait VfsBackend: Send + Sync {
+    fn open(&self, path: &Path, mode: VfsOpenMode) -> io::Result<Box<dyn VfsFile>>;
+
+    fn remove(&self, path: &Path) -> io::Result<()>;
+
+    fn rename(&self, old_path: &Path, new_path: &Path) -> io::Result<()>;
+
+    #[doc = "Retorna None se o caminho nao existe."]
+    fn stat(&self, path: &Path) -> Option<VfsStat>;
+
+    #[doc = "Deve retornar um erro [io::ErrorKind::AlreadyExists] se a pasta ja existe."]
+    fn mkdir(&self, path: &Path) -> io::Result<()>;
+
+    fn read_dir(&self, path: &Path, include_hidden: bool) -> io::Result<Vec<VfsDirEntry>>;
+
+    #[doc = "Chamado antes de carregar uma rom, backends com sandbox devem liberar acesso a pasta da rom."]
+    fn allow_dir(&self, _dir: &Path) {}
+
+    #[doc = "Chamado quando a rom e descarregada, remove as pastas liberadas por [VfsBackend::allow_dir]."]
+    fn reset_allowed_dirs(&self) {}
+}
+
+//resolve "." e ".." sem acessar o disco, assim o caminho pode ser comparado com a sandbox
+pub(crate) fn normalize_path(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            other => normalized.push(other),
+        }
+    }
+
+    normalized
+}
+
+pub(crate) fn is_hidden(name: &str) -> bool {
+    name.starts_with('.')
+}
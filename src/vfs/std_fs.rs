@@ -0,0 +1,337 @@
+use super::{is_hidden, normalize_path, VfsBackend, VfsDirEntry, VfsFile, VfsOpenMode, VfsStat};
+use crate::paths::Paths;
+use std::{
+    fs::{self, File, OpenOptions},
+    io,
+    path::{Path, PathBuf},
+    sync::RwLock,
+};
+
+impl VfsFile for File {
+    fn size(&mut self) -> io::Result<u64> {
+        Ok(self.metadata()?.len())
+    }
+
+    fn truncate(&mut self, length: u64) -> io::Result<()> {
+        self.set_len(length)
+    }
+}
+
+#[doc = "
+    # Backend que usa o sistema de arquivos do host
+
+    O núcleo so consegue acessar arquivos dentro de `roots`, se a lista estiver
+    vazia nenhuma restrição e aplicada. Os links simbólicos sao resolvidos antes da
+    verificação, um link que aponta para fora das pastas permitidas e recusado.
+"]
+#[derive(Debug, Default)]
+pub struct StdFsVfs {
+    roots: RwLock<Vec<PathBuf>>,
+    #[doc = "Pastas liberadas por [VfsBackend::allow_dir], removidas quando a rom e descarregada."]
+    content_roots: RwLock<Vec<PathBuf>>,
+}
+
+impl StdFsVfs {
+    pub fn new(roots: Vec<PathBuf>) -> Self {
+        let vfs = StdFsVfs::default();
+
+        for root in roots {
+            vfs.add_root(&root);
+        }
+
+        vfs
+    }
+
+    #[doc = "
+        Permite acesso apenas as pastas de [Paths]. A sandbox e opcional, para ativar use
+        [crate::core::CoreWrapper::set_vfs] com esse backend.
+    "]
+    pub fn from_paths(paths: &Paths) -> Self {
+        StdFsVfs::new(vec![
+            PathBuf::from(&paths.system),
+            PathBuf::from(&paths.save),
+            PathBuf::from(&paths.opt),
+            PathBuf::from(&paths.assets),
+            PathBuf::from(&paths.cheats),
+        ])
+    }
+
+    pub fn add_root(&self, root: &Path) {
+        let root = absolute_path(root);
+        let mut roots = self.roots.write().unwrap();
+
+        if !roots.contains(&root) {
+            roots.push(root);
+        }
+    }
+
+    pub fn get_roots(&self) -> Vec<PathBuf> {
+        self.roots.read().unwrap().clone()
+    }
+
+    fn check(&self, path: &Path) -> io::Result<PathBuf> {
+        let roots = self.roots.read().unwrap();
+
+        if roots.is_empty() {
+            return Ok(absolute_path(path));
+        }
+
+        let path = resolve_path(path)?;
+        let content_roots = self.content_roots.read().unwrap();
+
+        //as pastas também podem ser links, entao sao resolvidas do mesmo jeito
+        let is_allowed = roots
+            .iter()
+            .chain(content_roots.iter())
+            .filter_map(|root| resolve_path(root).ok())
+            .any(|root| path.starts_with(root));
+
+        if is_allowed {
+            Ok(path)
+        } else {
+            Err(permission_denied())
+        }
+    }
+}
+
+fn permission_denied() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::PermissionDenied,
+        "O caminho esta fora das pastas permitidas",
+    )
+}
+
+//resolve os links da parte do caminho que ja existe, o resto ainda vai ser criado
+fn resolve_path(path: &Path) -> io::Result<PathBuf> {
+    let path = absolute_path(path);
+    let mut existing = path.as_path();
+    let mut missing = Vec::new();
+
+    loop {
+        if let Ok(canonical) = existing.canonicalize() {
+            return Ok(missing
+                .iter()
+                .rev()
+                .fold(canonical, |resolved, name| resolved.join(name)));
+        }
+
+        //um link quebrado seria seguido ao criar o arquivo
+        if existing.symlink_metadata().is_ok() {
+            return Err(permission_denied());
+        }
+
+        match (existing.parent(), existing.file_name()) {
+            (Some(parent), Some(name)) => {
+                missing.push(name);
+                existing = parent;
+            }
+            _ => return Ok(path.clone()),
+        }
+    }
+}
+
+fn absolute_path(path: &Path) -> PathBuf {
+    if path.is_absolute() {
+        normalize_path(path)
+    } else {
+        let current_dir = std::env::current_dir().unwrap_or_default();
+        normalize_path(&current_dir.join(path))
+    }
+}
+
+impl VfsBackend for StdFsVfs {
+    fn open(&self, path: &Path, mode: VfsOpenMode) -> io::Result<Box<dyn VfsFile>> {
+        let path = self.check(path)?;
+        let create = mode.write && !mode.update_existing;
+
+        let file = OpenOptions::new()
+            .read(mode.read)
+            .write(mode.write)
+            .create(create)
+            .truncate(create)
+            .open(path)?;
+
+        Ok(Box::new(file))
+    }
+
+    fn remove(&self, path: &Path) -> io::Result<()> {
+        let path = self.check(path)?;
+
+        if path.is_dir() {
+            fs::remove_dir(path)
+        } else {
+            fs::remove_file(path)
+        }
+    }
+
+    fn rename(&self, old_path: &Path, new_path: &Path) -> io::Result<()> {
+        fs::rename(self.check(old_path)?, self.check(new_path)?)
+    }
+
+    fn stat(&self, path: &Path) -> Option<VfsStat> {
+        let metadata = fs::metadata(self.check(path).ok()?).ok()?;
+
+        Some(VfsStat {
+            is_dir: metadata.is_dir(),
+            size: metadata.len(),
+        })
+    }
+
+    fn mkdir(&self, path: &Path) -> io::Result<()> {
+        fs::create_dir(self.check(path)?)
+    }
+
+    fn read_dir(&self, path: &Path, include_hidden: bool) -> io::Result<Vec<VfsDirEntry>> {
+        let mut entries = Vec::new();
+
+        for entry in fs::read_dir(self.check(path)?)? {
+            let entry = entry?;
+            let name = entry.file_name().to_string_lossy().to_string();
+
+            if !include_hidden && is_hidden(&name) {
+                continue;
+            }
+
+            entries.push(VfsDirEntry {
+                name,
+                is_dir: entry.file_type()?.is_dir(),
+            });
+        }
+
+        Ok(entries)
+    }
+
+    fn allow_dir(&self, dir: &Path) {
+        if self.roots.read().unwrap().is_empty() {
+            return;
+        }
+
+        let dir = absolute_path(dir);
+        let mut content_roots = self.content_roots.write().unwrap();
+
+        if !content_roots.contains(&dir) {
+            content_roots.push(dir);
+        }
+    }
+
+    fn reset_allowed_dirs(&self) {
+        self.content_roots.write().unwrap().clear();
+    }
+}
+
+#[cfg(test)]
+mod test_std_fs {
+    use crate::vfs::{StdFsVfs, VfsBackend, VfsOpenMode};
+    use std::{
+        fs,
+        io::{ErrorKind, Read, Write},
+        path::{Path, PathBuf},
+    };
+
+    const ROOT: &str = "retro_out_test/vfs";
+
+    fn write_mode() -> VfsOpenMode {
+        VfsOpenMode {
+            read: true,
+            write: true,
+            update_existing: false,
+        }
+    }
+
+    #[test]
+    fn sandbox() {
+        fs::create_dir_all(ROOT).unwrap();
+        let vfs = StdFsVfs::new(vec![PathBuf::from(ROOT)]);
+
+        let path = Path::new(ROOT).join("sandbox.txt");
+        vfs.open(&path, write_mode())
+            .unwrap()
+            .write_all(b"retro_ab")
+            .unwrap();
+
+        let mut data = String::new();
+        vfs.open(&path, VfsOpenMode::from_raw(1))
+            .unwrap()
+            .read_to_string(&mut data)
+            .unwrap();
+
+        assert_eq!(data, "retro_ab");
+        assert_eq!(vfs.stat(&path).unwrap().size, 8);
+
+        let outside = Path::new(ROOT).join("../../Cargo.toml");
+        let erro = vfs.open(&outside, VfsOpenMode::from_raw(1)).err().unwrap();
+
+        assert_eq!(erro.kind(), ErrorKind::PermissionDenied);
+        assert!(vfs.stat(&outside).is_none());
+
+        vfs.allow_dir(Path::new("."));
+
+        assert!(vfs.stat(&outside).is_some());
+
+        vfs.reset_allowed_dirs();
+
+        assert!(vfs.stat(&outside).is_none());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn symlinks() {
+        let root = Path::new(ROOT).join("links");
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+
+        let vfs = StdFsVfs::new(vec![root.clone()]);
+
+        std::os::unix::fs::symlink("/", root.join("host")).unwrap();
+        std::os::unix::fs::symlink("/nao_existe/arquivo", root.join("quebrado")).unwrap();
+        fs::write(root.join("dentro.txt"), b"ok").unwrap();
+        std::os::unix::fs::symlink("dentro.txt", root.join("atalho")).unwrap();
+
+        let escape = vfs
+            .open(&root.join("host/etc/passwd"), VfsOpenMode::from_raw(1))
+            .err()
+            .unwrap();
+        assert_eq!(escape.kind(), ErrorKind::PermissionDenied);
+
+        let dangling = vfs
+            .open(&root.join("quebrado"), write_mode())
+            .err()
+            .unwrap();
+        assert_eq!(dangling.kind(), ErrorKind::PermissionDenied);
+
+        assert_eq!(vfs.stat(&root.join("atalho")).unwrap().size, 2);
+        vfs.open(&root.join("novo/../novo.txt"), write_mode())
+            .unwrap();
+    }
+
+    #[test]
+    fn directories() {
+        let root = Path::new(ROOT).join("dirs");
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(ROOT).unwrap();
+
+        let vfs = StdFsVfs::new(vec![PathBuf::from(ROOT)]);
+
+        vfs.mkdir(&root).unwrap();
+        assert_eq!(
+            vfs.mkdir(&root).unwrap_err().kind(),
+            ErrorKind::AlreadyExists
+        );
+
+        vfs.open(&root.join(".hidden"), write_mode()).unwrap();
+        vfs.open(&root.join("file"), write_mode()).unwrap();
+        vfs.mkdir(&root.join("sub")).unwrap();
+
+        let mut names: Vec<String> = vfs
+            .read_dir(&root, false)
+            .unwrap()
+            .into_iter()
+            .map(|entry| entry.name)
+            .collect();
+        names.sort();
+
+        assert_eq!(names, vec!["file", "sub"]);
+        assert_eq!(vfs.read_dir(&root, true).unwrap().len(), 3);
+        assert!(vfs.stat(&root.join("sub")).unwrap().is_dir);
+    }
+}
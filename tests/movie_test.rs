@@ -0,0 +1,33 @@
+use retro_ab::{erro_handle::ErroHandle, test_tools::constants::ROM_TEST_RELATIVE_PATH};
+use std::fs;
+
+mod common;
+
+#[test]
+fn movie_playback_is_deterministic() -> Result<(), ErroHandle> {
+    let ctx = common::core::setup()?;
+    let core = ctx.core();
+    let movie_path = "retro_out_test/movie/deterministic.rabm";
+
+    fs::create_dir_all("retro_out_test/movie").unwrap();
+
+    core.load_game(ROM_TEST_RELATIVE_PATH)?;
+    core.start_movie_recording(true)?;
+
+    for _ in 0..120 {
+        core.run()?;
+    }
+
+    let recorded_state = core.get_state_data()?;
+    core.stop_movie_recording(movie_path)?;
+
+    core.play_movie(movie_path)?;
+
+    for _ in 0..120 {
+        core.run()?;
+    }
+
+    assert_eq!(core.get_state_data()?, recorded_state);
+
+    core.unload_game()
+}